@@ -1,22 +1,88 @@
-use gamepad_gui::ToolkitBuilder;
+use gamepad_gui::{ToolkitBuilder, TkEvent, Widget, WidgetInput, Response, DrawContext};
+use gamepad_gui::sdl2::rect::Rect;
+
+// counts presses, drawn as a row of boxes
+struct Counter {
+    count: i32,
+}
+
+impl Widget for Counter {
+    fn measure(&mut self, ctx: &mut DrawContext) -> (u32, u32) {
+        let size = (32.0 * ctx.scale()) as u32;
+        (size * 10, size)
+    }
+    fn draw(&mut self, ctx: &mut DrawContext, rect: Rect, selected: bool) {
+        let theme = *ctx.theme();
+        let size = rect.height();
+        let canvas = ctx.canvas();
+        canvas.set_draw_color(if selected { theme.accent } else { theme.foreground });
+        for i in 0..10 {
+            let boxed = Rect::new(rect.x() + (i * size) as i32, rect.y(), size - 2, size - 2);
+            if (i as i32) < self.count {
+                canvas.fill_rect(boxed).unwrap();
+            } else {
+                canvas.draw_rect(boxed).unwrap();
+            }
+        }
+    }
+    fn input(&mut self, input: WidgetInput) -> Response {
+        match input {
+            WidgetInput::Press => {
+                self.count = (self.count + 1) % 11;
+                Response::Emit(self.count)
+            },
+            _ => Response::None,
+        }
+    }
+}
 
 fn main() {
-    let names_str = vec!["str names:", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth"];
+    let names_str = vec!["first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth", "first", "second", "third", "fourth"];
 
     let mut tk = ToolkitBuilder::new("Testing")
         .tab("whatever")
         .toggle("this is a toggle")
         .slider("slider", 3, 0, 5)
+        .text_input("name", "player")
+        .choice("governor", vec!["performance", "ondemand", "powersave"], 1)
         .button("idk")
         .button("stuff")
+        .submenu("more settings")
+        .toggle("nested toggle")
+        .submenu("even more")
+        .button("deep button")
+        .end_submenu()
+        .end_submenu()
+        .tab("tiles")
+        .grid(3)
+        .button("one").button("two").button("three")
+        .button("four").button("five").button("six")
+        .button("seven").button("eight")
+        .end_grid()
+        .row()
+        .button("left").button("middle").button("right")
+        .end_row()
+        .button("under the tiles")
         .tab("another tab")
         .button("i am a button")
+        .widget(Box::new(Counter { count: 0 }))
+        .button_with("rename this tab", |ctx| { ctx.set_tab_label(2, "renamed"); })
+        .button_with("quit", |ctx| {
+            ctx.confirm_with("Really quit?", |ctx| {
+                if let TkEvent::DialogResult(_, true) = ctx.event() {
+                    ctx.quit();
+                }
+            });
+        })
+        .separator()
+        .paragraph("Buttons below are made from a Vec<&str>, this text can't be selected and is wrapped to the width of the window.")
         .tab("tab from vec<str>")
+        .label("str names:")
         .buttons_vec(names_str)
         .build();
 
     while tk.tick() {
-        for ev in tk.poll_events() {
+        while let Some(ev) = tk.poll_events() {
             println!("{:#?}", ev);
         }
     }
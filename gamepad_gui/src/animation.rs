@@ -0,0 +1,206 @@
+use std::time::{Duration, Instant};
+
+// How an animation moves from start to end
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    // fast at first, then slows down
+    EaseOut,
+    // slow at both ends
+    EaseInOut,
+}
+
+impl Easing {
+    // `t` goes from 0.0 to 1.0
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => if t < 0.5 {
+                4.0 * t * t * t
+            } else {
+                1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AnimationConfig {
+    // when off everything jumps straight to where it's going
+    // and flicks stop with the finger
+    pub enabled: bool,
+    pub duration: Duration,
+    pub easing: Easing,
+    // how much of a flick's speed is left after a second
+    pub friction: f32,
+}
+
+impl Default for AnimationConfig {
+    fn default() -> AnimationConfig {
+        AnimationConfig {
+            enabled: true,
+            duration: Duration::from_millis(150),
+            easing: Easing::EaseOut,
+            friction: 0.2,
+        }
+    }
+}
+
+// A value that moves to a new target over time instead of jumping there,
+// starting from wherever it was when the target changed
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Tween {
+    from: f32,
+    to: f32,
+    start: Instant,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Tween {
+    pub fn new(value: f32) -> Tween {
+        Tween {
+            from: value,
+            to: value,
+            start: Instant::now(),
+            duration: Duration::ZERO,
+            easing: Easing::Linear,
+        }
+    }
+    pub fn value(&self, now: Instant) -> f32 {
+        if self.duration.is_zero() {
+            return self.to;
+        }
+        let t = now.saturating_duration_since(self.start).as_secs_f32() / self.duration.as_secs_f32();
+        self.from + (self.to - self.from) * self.easing.apply(t)
+    }
+    pub fn moving(&self, now: Instant) -> bool {
+        self.from != self.to && now < self.start + self.duration
+    }
+    pub fn set(&mut self, to: f32, cfg: &AnimationConfig, now: Instant) {
+        if to == self.to {
+            return;
+        }
+        self.from = self.value(now);
+        self.to = to;
+        self.start = now;
+        self.duration = if cfg.enabled { cfg.duration } else { Duration::ZERO };
+        self.easing = cfg.easing;
+    }
+    // animates from `from` to `to`, wherever it was before
+    pub fn start(&mut self, from: f32, to: f32, cfg: &AnimationConfig, now: Instant) {
+        self.to = from;
+        self.duration = Duration::ZERO;
+        self.set(to, cfg, now);
+    }
+    pub fn jump(&mut self, to: f32) {
+        self.from = to;
+        self.to = to;
+        self.duration = Duration::ZERO;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linear(duration: u64) -> AnimationConfig {
+        AnimationConfig {
+            duration: Duration::from_millis(duration),
+            easing: Easing::Linear,
+            ..AnimationConfig::default()
+        }
+    }
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn easings_go_from_zero_to_one() {
+        for easing in [Easing::Linear, Easing::EaseOut, Easing::EaseInOut] {
+            assert_near(easing.apply(0.0), 0.0);
+            assert_near(easing.apply(1.0), 1.0);
+            // and stay there outside of it
+            assert_near(easing.apply(-1.0), 0.0);
+            assert_near(easing.apply(2.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn easings_differ_in_between() {
+        assert_near(Easing::Linear.apply(0.5), 0.5);
+        assert_near(Easing::EaseOut.apply(0.5), 0.875);
+        assert_near(Easing::EaseInOut.apply(0.25), 0.0625);
+        assert_near(Easing::EaseInOut.apply(0.5), 0.5);
+        assert_near(Easing::EaseInOut.apply(0.75), 0.9375);
+    }
+
+    #[test]
+    fn tween_moves_to_the_target_over_the_duration() {
+        let now = Instant::now();
+        let mut tween = Tween::new(0.0);
+        tween.set(10.0, &linear(100), now);
+        assert_near(tween.value(now), 0.0);
+        assert_near(tween.value(now + Duration::from_millis(50)), 5.0);
+        assert!(tween.moving(now + Duration::from_millis(50)));
+        assert_near(tween.value(now + Duration::from_millis(100)), 10.0);
+        assert!(!tween.moving(now + Duration::from_millis(100)));
+        assert_near(tween.value(now + Duration::from_millis(500)), 10.0);
+    }
+
+    #[test]
+    fn set_partway_starts_from_where_it_is() {
+        let now = Instant::now();
+        let half = now + Duration::from_millis(50);
+        let mut tween = Tween::new(0.0);
+        tween.set(10.0, &linear(100), now);
+        tween.set(20.0, &linear(100), half);
+        assert_near(tween.value(half), 5.0);
+        assert_near(tween.value(half + Duration::from_millis(50)), 12.5);
+        assert_near(tween.value(half + Duration::from_millis(100)), 20.0);
+    }
+
+    #[test]
+    fn set_to_the_same_target_keeps_going() {
+        let now = Instant::now();
+        let half = now + Duration::from_millis(50);
+        let mut tween = Tween::new(0.0);
+        tween.set(10.0, &linear(100), now);
+        tween.set(10.0, &linear(100), half);
+        assert_near(tween.value(half), 5.0);
+        assert_near(tween.value(now + Duration::from_millis(100)), 10.0);
+    }
+
+    #[test]
+    fn set_jumps_when_animations_are_off() {
+        let now = Instant::now();
+        let mut tween = Tween::new(0.0);
+        tween.set(10.0, &AnimationConfig { enabled: false, ..linear(100) }, now);
+        assert_near(tween.value(now), 10.0);
+        assert!(!tween.moving(now));
+    }
+
+    #[test]
+    fn start_goes_from_the_given_value() {
+        let now = Instant::now();
+        let mut tween = Tween::new(1.0);
+        tween.start(0.0, 1.0, &linear(100), now);
+        assert_near(tween.value(now), 0.0);
+        assert!(tween.moving(now));
+        assert_near(tween.value(now + Duration::from_millis(50)), 0.5);
+        assert_near(tween.value(now + Duration::from_millis(100)), 1.0);
+    }
+
+    #[test]
+    fn jump_stops_right_away() {
+        let now = Instant::now();
+        let mut tween = Tween::new(0.0);
+        tween.set(10.0, &linear(100), now);
+        tween.jump(3.0);
+        assert_near(tween.value(now), 3.0);
+        assert!(!tween.moving(now));
+    }
+}
@@ -0,0 +1,327 @@
+use std::borrow::Cow;
+
+use sdl2::{
+    rect::{Rect, Point},
+    render::{Canvas, Texture},
+    video::Window,
+};
+
+use crate::{Buttonish, DrawContext, Id, InternalTkEvent, Page, TkEvent, Theme};
+use crate::font::TextRenderer;
+
+// Lays its widgets out in equally sized cells, `columns` to a row, or all
+// in one row if `columns` is 0. Takes up one line of the tab and has its
+// own selection, left/right goes through the cells in reading order and
+// wraps around, up/down moves between rows and leaves at the first and last.
+pub(crate) struct Container {
+    id: Id,
+    children: Page,
+    columns: usize,
+    pos: usize,
+    cell: (u32, u32),
+    gap: u32,
+    rect: Option<Rect>,
+    // what was visible of each cell on the last draw in window coordinates,
+    // for touch input
+    screen_rects: Vec<Option<Rect>>,
+}
+
+impl Container {
+    pub fn new(mut children: Page, columns: usize, gap: u32) -> Container {
+        let columns = if columns == 0 { std::cmp::max(children.len(), 1) } else { columns };
+        for child in children.iter_mut() {
+            child.set_y(0);
+        }
+        let mut container = Container {
+            id: Id::unique(),
+            children,
+            columns,
+            pos: 0,
+            cell: (0, 0),
+            gap,
+            rect: Some(Rect::new(0, 0, 1, 1)),
+            screen_rects: Vec::new(),
+        };
+        container.fit();
+        container.pos = container.first_focusable(0, 1).unwrap_or(0);
+        container
+    }
+
+    // sizes the cells to the biggest child
+    fn fit(&mut self) {
+        let width = self.children.iter().map(|c| c.rect().width()).max().unwrap_or(0);
+        let height = self.children.iter().map(|c| c.rect().height()).max().unwrap_or(0);
+        let rows = self.rows() as u32;
+        let cols = std::cmp::min(self.columns, self.children.len()) as u32;
+        let y = self.rect.unwrap().y();
+        self.cell = (width, height);
+        self.rect = Some(Rect::new(
+            0, y,
+            std::cmp::max(cols * width + cols.saturating_sub(1) * self.gap, 1),
+            std::cmp::max(rows * height + rows.saturating_sub(1) * self.gap, 1),
+        ));
+    }
+
+    // relative to the container's top left corner
+    fn cell_rect(&self, index: usize) -> Rect {
+        let (col, row) = ((index % self.columns) as u32, (index / self.columns) as u32);
+        Rect::new(
+            (col * (self.cell.0 + self.gap)) as i32,
+            (row * (self.cell.1 + self.gap)) as i32,
+            self.cell.0,
+            self.cell.1,
+        )
+    }
+    fn rows(&self) -> usize {
+        self.children.len().div_ceil(self.columns)
+    }
+    fn focusable_at(&self, index: usize) -> bool {
+        self.children.get(index).is_some_and(|c| c.focusable())
+    }
+    // the first focusable cell from `from` going `dir`, without wrapping
+    fn first_focusable(&self, from: usize, dir: i32) -> Option<usize> {
+        let mut i = from as i32;
+        while i >= 0 && (i as usize) < self.children.len() {
+            if self.focusable_at(i as usize) {
+                return Some(i as usize);
+            }
+            i += dir;
+        }
+        None
+    }
+    // the focusable cell in `row` closest to `col`
+    fn closest_in_row(&self, row: usize, col: usize) -> Option<usize> {
+        let start = row * self.columns;
+        let end = std::cmp::min(start + self.columns, self.children.len());
+        (start..end)
+            .filter(|i| self.focusable_at(*i))
+            .min_by_key(|i| (*i as i32 - (start + col) as i32).abs())
+    }
+    fn child(&self) -> Option<&dyn Buttonish> {
+        self.children.get(self.pos).map(|c| c.as_ref())
+    }
+    fn child_mut(&mut self) -> Option<&mut Box<dyn Buttonish>> {
+        self.children.get_mut(self.pos)
+    }
+}
+
+impl Buttonish for Container {
+    fn draw(&mut self, canvas: &mut Canvas<Window>, theme: &Theme, text: &mut TextRenderer, selected: bool, offset_y: i32) {
+        let rect = self.rect.unwrap();
+        let old = canvas.viewport();
+        let old_clip = canvas.clip_rect();
+        self.screen_rects.clear();
+        for i in 0..self.children.len() {
+            let cell = self.cell_rect(i);
+            // each child thinks it's at the top left of its own viewport
+            let viewport = Rect::new(
+                old.x() + rect.x() + cell.x(),
+                old.y() + rect.y() + offset_y + cell.y(),
+                cell.width(),
+                cell.height(),
+            );
+            // cells scrolled partly out of the tab are cut off at its edge,
+            // the clip rect goes by the cell's viewport
+            let visible = viewport.intersection(old);
+            self.screen_rects.push(visible);
+            let visible = match visible {
+                Some(visible) => visible,
+                None => continue,
+            };
+            canvas.set_viewport(viewport);
+            canvas.set_clip_rect(Rect::new(
+                visible.x() - viewport.x(),
+                visible.y() - viewport.y(),
+                visible.width(),
+                visible.height(),
+            ));
+            self.children[i].draw(canvas, theme, text, selected && i == self.pos, 0);
+        }
+        canvas.set_viewport(old);
+        canvas.set_clip_rect(old_clip);
+    }
+    fn draw_overlay(&mut self, canvas: &mut Canvas<Window>, theme: &Theme, text: &mut TextRenderer) {
+        if let Some(child) = self.child_mut() {
+            child.draw_overlay(canvas, theme, text);
+        }
+    }
+    fn captures_input(&self) -> bool { self.child().is_some_and(|c| c.captures_input()) }
+    fn holds_input(&self) -> bool { self.child().is_none_or(|c| c.holds_input()) }
+    fn focusable(&self) -> bool { self.children.iter().any(|c| c.focusable()) }
+    fn is_grid(&self) -> bool { true }
+    fn action(&mut self, tab: &Id, ev: &InternalTkEvent) -> Option<TkEvent> {
+        self.child_mut()?.action(tab, ev)
+    }
+    fn navigate(&mut self, dx: i32, dy: i32) -> bool {
+        let len = self.children.len();
+        if len == 0 {
+            return false;
+        }
+        if dx != 0 {
+            let dir = dx.signum();
+            let mut i = self.pos;
+            for _ in 0..len {
+                i = (i as i32 + dir).rem_euclid(len as i32) as usize;
+                if self.focusable_at(i) {
+                    break;
+                }
+            }
+            let moved = i != self.pos;
+            self.pos = i;
+            return moved;
+        }
+        // page up/down skips over the whole thing
+        if dy.abs() != 1 {
+            return false;
+        }
+        let col = self.pos % self.columns;
+        let mut row = (self.pos / self.columns) as i32;
+        loop {
+            row += dy;
+            if row < 0 || row as usize >= self.rows() {
+                return false;
+            }
+            if let Some(i) = self.closest_in_row(row as usize, col) {
+                self.pos = i;
+                return true;
+            }
+        }
+    }
+    fn enter(&mut self, from: i32) {
+        let col = self.pos % self.columns;
+        let found = if from > 0 {
+            (0..self.rows()).find_map(|row| self.closest_in_row(row, col))
+        } else {
+            (0..self.rows()).rev().find_map(|row| self.closest_in_row(row, col))
+        };
+        if let Some(i) = found {
+            self.pos = i;
+        }
+    }
+    fn select_at(&mut self, x: i32, y: i32) -> bool {
+        let hit = self.screen_rects.iter().position(|r| r.is_some_and(|r| r.contains_point(Point::new(x, y))));
+        match hit {
+            Some(i) if i != self.pos && self.focusable_at(i) => {
+                self.pos = i;
+                true
+            },
+            _ => false,
+        }
+    }
+    fn focus_id(&self) -> &Id { self.child().map_or(&self.id, |c| c.focus_id()) }
+    fn focus_rect(&self) -> Rect {
+        let rect = self.rect.unwrap();
+        let cell = self.cell_rect(self.pos);
+        Rect::new(rect.x() + cell.x(), rect.y() + cell.y(), cell.width(), cell.height())
+    }
+    // the selected child's, for the breadcrumb when it's a submenu
+    fn name(&self) -> &str { self.child().map_or("", |c| c.name()) }
+    fn id(&self) -> &Id { &self.id }
+    fn set_id(&mut self, id: Id) { self.id = id }
+    fn rect(&self) -> Rect { self.rect.unwrap() }
+    fn has_label(&self) -> bool { false }
+    fn set_label(&mut self, _: Cow<'static, str>, _: Texture, _: u32) {}
+    fn set_y(&mut self, y: i32) { self.rect.as_mut().unwrap().set_y(y) }
+    fn page(&self) -> Option<&[Box<dyn Buttonish>]> { Some(&self.children) }
+    fn page_mut(&mut self) -> Option<&mut Page> { Some(&mut self.children) }
+    // submenus inside open like they would outside
+    fn take_page(&mut self) -> Option<Page> { self.child_mut()?.take_page() }
+    fn restore_page(&mut self, page: Page) {
+        if let Some(child) = self.child_mut() {
+            child.restore_page(page);
+        }
+    }
+    fn rescale(&mut self, ctx: &mut DrawContext) {
+        for child in self.children.iter_mut() {
+            child.rescale(ctx);
+            child.set_y(0);
+        }
+        self.fit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Picture, Separator};
+
+    // focusable and doesn't need a renderer
+    fn picture() -> Box<dyn Buttonish> {
+        Box::new(Picture {
+            name: Cow::Borrowed(""),
+            id: Id::unique(),
+            image: None,
+            size: (10, 10),
+            rect: Some(Rect::new(0, 0, 10, 10)),
+        })
+    }
+
+    fn separator() -> Box<dyn Buttonish> {
+        Box::new(Separator::new(10))
+    }
+
+    #[test]
+    fn left_and_right_wrap_in_reading_order() {
+        // 0 1 _
+        // 3 _
+        let mut grid = Container::new(vec![picture(), picture(), separator(), picture(), separator()], 3, 0);
+        assert!(grid.navigate(1, 0));
+        assert_eq!(grid.pos, 1);
+        assert!(grid.navigate(1, 0));
+        assert_eq!(grid.pos, 3);
+        assert!(grid.navigate(1, 0));
+        assert_eq!(grid.pos, 0);
+        assert!(grid.navigate(-1, 0));
+        assert_eq!(grid.pos, 3);
+    }
+
+    #[test]
+    fn left_and_right_with_one_cell_do_not_move() {
+        let mut row = Container::new(vec![separator(), picture(), separator()], 0, 0);
+        assert_eq!(row.pos, 1);
+        assert!(!row.navigate(1, 0));
+        assert!(!row.navigate(-1, 0));
+        assert_eq!(row.pos, 1);
+    }
+
+    #[test]
+    fn up_and_down_keep_the_column_and_leave_at_the_ends() {
+        // 0 1 2
+        // _ _ 5
+        // 6
+        let mut grid = Container::new(vec![
+            picture(), picture(), picture(),
+            separator(), separator(), picture(),
+            picture(),
+        ], 3, 0);
+        grid.pos = 1;
+        assert!(grid.navigate(0, 1));
+        assert_eq!(grid.pos, 5);
+        assert!(grid.navigate(0, 1));
+        assert_eq!(grid.pos, 6);
+        assert!(!grid.navigate(0, 1));
+        assert_eq!(grid.pos, 6);
+        assert!(grid.navigate(0, -1));
+        assert_eq!(grid.pos, 5);
+        assert!(grid.navigate(0, -1));
+        assert_eq!(grid.pos, 2);
+        assert!(!grid.navigate(0, -1));
+        // page up/down skips the whole grid
+        assert!(!grid.navigate(0, 10));
+    }
+
+    #[test]
+    fn enter_picks_the_row_on_the_side_it_came_from() {
+        // _ 1 2
+        // 3 4 _
+        let mut grid = Container::new(vec![separator(), picture(), picture(), picture(), picture(), separator()], 3, 0);
+        grid.pos = 2;
+        grid.enter(1);
+        assert_eq!(grid.pos, 2);
+        grid.enter(-1);
+        assert_eq!(grid.pos, 4);
+        grid.pos = 3;
+        grid.enter(1);
+        assert_eq!(grid.pos, 1);
+    }
+}
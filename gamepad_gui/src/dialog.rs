@@ -0,0 +1,196 @@
+use sdl2::{
+    rect::{Rect, Point},
+    render::{Canvas, Texture},
+    video::Window,
+};
+
+use crate::{DrawContext, Id, InternalTkEvent, Theme, destroy};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum DialogKind {
+    Confirm,
+    Alert,
+    // 0.0 to 1.0
+    Progress(f32),
+}
+
+// A box in the middle of the screen with a message and either a row of
+// buttons or a progress bar.
+pub(crate) struct Dialog {
+    pub id: Id,
+    kind: DialogKind,
+    // the text is kept for rendering it again at another scale
+    message: (String, Option<Texture>),
+    // the first one means yes
+    buttons: Vec<(String, Texture)>,
+    selected: usize,
+    // where the buttons ended up on the last draw, for touch input
+    button_rects: Vec<Rect>,
+}
+
+impl Dialog {
+    pub fn new(id: Id, kind: DialogKind, message: (String, Texture), buttons: Vec<(String, Texture)>) -> Dialog {
+        Dialog {
+            id,
+            kind,
+            message: (message.0, Some(message.1)),
+            // confirm dialogs default to no, it's usually something destructive
+            selected: buttons.len().saturating_sub(1),
+            buttons,
+            button_rects: Vec::new(),
+        }
+    }
+
+    pub fn rescale(&mut self, ctx: &mut DrawContext) {
+        let message = ctx.render_text(&self.message.0);
+        destroy(self.message.1.replace(message));
+        for (label, text) in self.buttons.iter_mut() {
+            destroy(Some(std::mem::replace(text, ctx.render_text(label))));
+        }
+    }
+
+    pub fn set_progress(&mut self, progress: f32) -> bool {
+        if let DialogKind::Progress(_) = self.kind {
+            self.kind = DialogKind::Progress(progress.clamp(0.0, 1.0));
+            true
+        } else {
+            false
+        }
+    }
+
+    // returns the result once the dialog is answered,
+    // progress dialogs can only be closed by the app
+    pub fn handle(&mut self, ev: &InternalTkEvent) -> Option<bool> {
+        if self.buttons.is_empty() {
+            return None;
+        }
+        match ev {
+            InternalTkEvent::ChangeTabPos(d) | InternalTkEvent::ChangeBtnPos(d) => {
+                let len = self.buttons.len() as i32;
+                self.selected = (self.selected as i32 + d.signum()).rem_euclid(len) as usize;
+                None
+            },
+            InternalTkEvent::Press => Some(self.selected == 0),
+            InternalTkEvent::Back => Some(self.kind == DialogKind::Alert),
+            InternalTkEvent::TouchPress(x, y) => {
+                let hit = self.button_rects.iter().position(|rect| rect.contains_point(Point::new(*x, *y)))?;
+                Some(hit == 0)
+            },
+            _ => None,
+        }
+    }
+
+    pub fn draw(&mut self, canvas: &mut Canvas<Window>, theme: &Theme) {
+        let (width, height) = canvas.output_size().unwrap();
+        let padding = theme.padding;
+        let message = self.message.1.as_ref().unwrap().query();
+        let line = message.height;
+
+        // message on the first line, buttons or the progress bar on the second
+        let inner_width = std::cmp::max(message.width, width / 2);
+        let outer = Rect::from_center(
+            Point::new(width as i32 / 2, height as i32 / 2),
+            inner_width + padding * 2,
+            line * 2 + padding * 3,
+        );
+        canvas.set_draw_color(theme.background);
+        canvas.fill_rect(outer).unwrap();
+        canvas.set_draw_color(theme.foreground);
+        canvas.draw_rect(outer).unwrap();
+
+        theme.tint(self.message.1.as_mut().unwrap(), false);
+        let x = outer.x() + padding as i32;
+        canvas.copy(self.message.1.as_ref().unwrap(), None, Rect::new(x, outer.y() + padding as i32, message.width, message.height)).unwrap();
+
+        let y = outer.y() + (padding * 2 + line) as i32;
+        if let DialogKind::Progress(progress) = self.kind {
+            let bar = Rect::new(x, y, inner_width, line);
+            canvas.draw_rect(bar).unwrap();
+            let filled = (inner_width as f32 * progress) as u32;
+            if filled > 0 {
+                canvas.fill_rect(Rect::new(x, y, filled, line)).unwrap();
+            }
+            return;
+        }
+
+        self.button_rects.clear();
+        let slot = inner_width / self.buttons.len() as u32;
+        for (i, (_, button)) in self.buttons.iter_mut().enumerate() {
+            let selected = i == self.selected;
+            let attr = button.query();
+            let center = Point::new(x + (slot * i as u32 + slot / 2) as i32, y + line as i32 / 2);
+            let rect = Rect::from_center(center, attr.width + padding * 2, attr.height);
+            if selected {
+                canvas.set_draw_color(theme.accent);
+                canvas.draw_rect(rect).unwrap();
+            }
+            theme.tint(button, selected);
+            canvas.copy(button, None, Rect::from_center(center, attr.width, attr.height)).unwrap();
+            self.button_rects.push(rect);
+        }
+    }
+}
+
+impl Drop for Dialog {
+    fn drop(&mut self) {
+        destroy(self.message.1.take());
+        self.buttons.drain(..).for_each(|(_, text)| destroy(Some(text)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::{pixels::PixelFormatEnum, surface::Surface};
+
+    // a software renderer for the textures, it has to outlive the dialog
+    // so it comes first and gets dropped last
+    fn dialog(kind: DialogKind, buttons: usize) -> (Canvas<Surface<'static>>, Dialog) {
+        let canvas = Surface::new(1, 1, PixelFormatEnum::RGBA8888).unwrap().into_canvas().unwrap();
+        let creator = canvas.texture_creator();
+        let texture = || creator.create_texture_target(PixelFormatEnum::RGBA8888, 1, 1).unwrap();
+        let buttons = (0..buttons).map(|i| (i.to_string(), texture())).collect();
+        let dialog = Dialog::new(Id::from("dialog"), kind, ("message".to_string(), texture()), buttons);
+        (canvas, dialog)
+    }
+
+    #[test]
+    fn selection_starts_on_no_and_wraps() {
+        let (_canvas, mut dialog) = dialog(DialogKind::Confirm, 2);
+        assert_eq!(dialog.selected, 1);
+        assert_eq!(dialog.handle(&InternalTkEvent::ChangeTabPos(1)), None);
+        assert_eq!(dialog.selected, 0);
+        assert_eq!(dialog.handle(&InternalTkEvent::ChangeBtnPos(-1)), None);
+        assert_eq!(dialog.selected, 1);
+        assert_eq!(dialog.handle(&InternalTkEvent::Press), Some(false));
+        dialog.handle(&InternalTkEvent::ChangeTabPos(-1));
+        assert_eq!(dialog.handle(&InternalTkEvent::Press), Some(true));
+    }
+
+    #[test]
+    fn back_cancels_confirms_and_dismisses_alerts() {
+        let (_canvas, mut confirm) = dialog(DialogKind::Confirm, 2);
+        assert_eq!(confirm.handle(&InternalTkEvent::Back), Some(false));
+        let (_canvas, mut alert) = dialog(DialogKind::Alert, 1);
+        assert_eq!(alert.handle(&InternalTkEvent::Back), Some(true));
+        assert_eq!(alert.handle(&InternalTkEvent::Press), Some(true));
+    }
+
+    #[test]
+    fn touch_answers_with_the_button_under_it() {
+        let (_canvas, mut dialog) = dialog(DialogKind::Confirm, 2);
+        dialog.button_rects = vec![Rect::new(0, 0, 10, 10), Rect::new(20, 0, 10, 10)];
+        assert_eq!(dialog.handle(&InternalTkEvent::TouchPress(15, 5)), None);
+        assert_eq!(dialog.handle(&InternalTkEvent::TouchPress(25, 5)), Some(false));
+        assert_eq!(dialog.handle(&InternalTkEvent::TouchPress(5, 5)), Some(true));
+    }
+
+    #[test]
+    fn progress_dialogs_ignore_input() {
+        let (_canvas, mut dialog) = dialog(DialogKind::Progress(0.0), 0);
+        assert_eq!(dialog.handle(&InternalTkEvent::Press), None);
+        assert_eq!(dialog.handle(&InternalTkEvent::Back), None);
+        assert!(dialog.set_progress(2.0));
+        assert_eq!(dialog.kind, DialogKind::Progress(1.0));
+    }
+}
@@ -0,0 +1,170 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use sdl2::{
+    pixels::{Color, PixelFormatEnum},
+    render::{Texture, TextureCreator},
+    surface::Surface,
+    ttf::{Font, Sdl2TtfContext},
+    video::WindowContext,
+};
+
+// fonts borrow the context, so there's one for the whole process
+// and it lives as long as that
+static TTF: OnceLock<Result<Sdl2TtfContext, String>> = OnceLock::new();
+
+fn ttf() -> Result<&'static Sdl2TtfContext, String> {
+    TTF.get_or_init(|| sdl2::ttf::init().map_err(|e| e.to_string())).as_ref().map_err(|e| e.clone())
+}
+
+#[cfg(feature = "embedded-font")]
+static EMBEDDED_FONT: &[u8] = include_bytes!("../fonts/DejaVuSans.ttf");
+
+// Where to look for a font, in order: explicit paths, then fontconfig
+// families through fc-match, then the embedded font if the `embedded-font`
+// feature is on.
+#[derive(Debug, Clone)]
+pub struct FontConfig {
+    paths: Vec<PathBuf>,
+    families: Vec<String>,
+    size: u16,
+}
+
+impl Default for FontConfig {
+    fn default() -> FontConfig {
+        FontConfig::new()
+            .path("/usr/share/fonts/liberation/LiberationSans-Regular.ttf")
+            .path("/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf")
+            .path("/usr/share/fonts/TTF/DejaVuSans.ttf")
+            .path("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf")
+            .family("sans-serif")
+    }
+}
+
+impl FontConfig {
+    // nothing to look through, only the embedded font if it's enabled
+    pub fn new() -> FontConfig {
+        FontConfig {
+            paths: Vec::new(),
+            families: Vec::new(),
+            size: 28,
+        }
+    }
+    pub fn path<P: Into<PathBuf>>(mut self, path: P) -> FontConfig {
+        self.paths.push(path.into());
+        self
+    }
+    pub fn family(mut self, family: &str) -> FontConfig {
+        self.families.push(family.to_string());
+        self
+    }
+    // point size at 480 pixels tall, scaled along with the window
+    pub fn size(mut self, size: u16) -> FontConfig {
+        self.size = size;
+        self
+    }
+
+    fn fc_match(family: &str) -> Option<PathBuf> {
+        let out = Command::new("fc-match").arg("-f").arg("%{file}").arg(family).output().ok()?;
+        if !out.status.success() || out.stdout.is_empty() {
+            return None;
+        }
+        Some(PathBuf::from(String::from_utf8(out.stdout).ok()?))
+    }
+
+    pub(crate) fn load(&self, ttf: &'static Sdl2TtfContext, scale: f32) -> Result<Font<'static, 'static>, String> {
+        let size = (self.size as f32 * scale) as u16;
+
+        let found = self.paths.iter()
+            .cloned()
+            .chain(self.families.iter().filter_map(|f| FontConfig::fc_match(f)))
+            .find_map(|path| ttf.load_font(&path, size).ok());
+        if let Some(font) = found {
+            return Ok(font);
+        }
+
+        #[cfg(feature = "embedded-font")]
+        {
+            let rwops = sdl2::rwops::RWops::from_bytes(EMBEDDED_FONT)?;
+            ttf.load_font_from_rwops(rwops, size)
+        }
+        #[cfg(not(feature = "embedded-font"))]
+        Err(format!("no usable font, tried {:?} and families {:?}, \
+                     enable the embedded-font feature or add a font path", self.paths, self.families))
+    }
+}
+
+// sdl_ttf refuses to render empty strings and can't pass NULs on to the font
+fn printable(input: &str) -> Cow<'_, str> {
+    let input = if input.contains('\0') { Cow::Owned(input.replace('\0', "")) } else { Cow::Borrowed(input) };
+    if input.is_empty() { Cow::Borrowed(" ") } else { input }
+}
+
+pub(crate) struct TextRenderer {
+    ttf: &'static Sdl2TtfContext,
+    config: FontConfig,
+    // with the scale it was loaded at
+    font: (f32, Font<'static, 'static>),
+    creator: TextureCreator<WindowContext>,
+}
+
+impl TextRenderer {
+    pub fn new(creator: TextureCreator<WindowContext>, scale: f32) -> Result<TextRenderer, String> {
+        let ttf = ttf()?;
+        let config = FontConfig::default();
+        let font = config.load(ttf, scale)?;
+        Ok(TextRenderer {
+            ttf,
+            config,
+            font: (scale, font),
+            creator,
+        })
+    }
+    // for anything else that needs turning into a texture
+    pub fn creator(&self) -> &TextureCreator<WindowContext> {
+        &self.creator
+    }
+    // keeps the font it had if there's nothing usable in `config`
+    pub fn set_config(&mut self, config: FontConfig, scale: f32) -> Result<(), String> {
+        self.font = (scale, config.load(self.ttf, scale)?);
+        self.config = config;
+        Ok(())
+    }
+    fn font(&mut self, scale: f32) -> &Font<'static, 'static> {
+        if self.font.0 != scale {
+            // it loaded before, so this only fails if the file's gone since
+            match self.config.load(self.ttf, scale) {
+                Ok(font) => self.font = (scale, font),
+                Err(e) => eprintln!("gamepad_gui: can't load the font at the new size, {}", e),
+            }
+        }
+        &self.font.1
+    }
+    fn texture(&self, surface: Result<Surface, String>) -> Texture {
+        let texture = surface.and_then(|surface| {
+            self.creator.create_texture_from_surface(&surface).map_err(|e| e.to_string())
+        });
+        match texture {
+            Ok(texture) => texture,
+            // blank instead, labels aren't worth bringing the whole ui down
+            Err(e) => {
+                eprintln!("gamepad_gui: can't render text, {}", e);
+                let blank = Surface::new(1, 1, PixelFormatEnum::RGBA32).unwrap();
+                self.creator.create_texture_from_surface(&blank).unwrap()
+            },
+        }
+    }
+    pub fn render(&mut self, input: &str, scale: f32) -> Texture {
+        let input = printable(input);
+        let surface = self.font(scale).render(&input).blended(Color::RGBA(255, 255, 255, 255));
+        self.texture(surface.map_err(|e| e.to_string()))
+    }
+    // breaks lines on newlines and wherever they'd get wider than `width`
+    pub fn render_wrapped(&mut self, input: &str, scale: f32, width: u32) -> Texture {
+        let input = printable(input);
+        let surface = self.font(scale).render(&input).blended_wrapped(Color::RGBA(255, 255, 255, 255), width);
+        self.texture(surface.map_err(|e| e.to_string()))
+    }
+}
@@ -0,0 +1,113 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use sdl2::pixels::PixelFormatEnum;
+
+use crate::{Toolkit, TkEvent, InputAction};
+
+fn save_png(path: &Path, width: u32, height: u32, pixels: &[u8]) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(pixels).map_err(|e| e.to_string())
+}
+
+fn load_png(path: &Path) -> Result<(u32, u32, Vec<u8>), String> {
+    let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut reader = png::Decoder::new(file).read_info().map_err(|e| e.to_string())?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).map_err(|e| e.to_string())?;
+    if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+        return Err(format!("{}: golden images have to be 8 bit RGBA", path.display()));
+    }
+    pixels.truncate(info.buffer_size());
+    Ok((info.width, info.height, pixels))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Tap(InputAction),
+    Hold(InputAction),
+    Release(InputAction),
+}
+
+impl From<InputAction> for Step {
+    fn from(action: InputAction) -> Step {
+        Step::Tap(action)
+    }
+}
+
+impl Toolkit {
+    // redraws everything and returns the frame as RGBA pixels
+    pub fn frame(&mut self) -> (u32, u32, Vec<u8>) {
+        self.draw();
+        let (width, height) = self.canvas.output_size().unwrap();
+        let pixels = self.canvas.read_pixels(None, PixelFormatEnum::RGBA32).unwrap();
+        self.canvas.present();
+        (width, height, pixels)
+    }
+
+    pub fn save_frame<P: AsRef<Path>>(&mut self, path: P) -> Result<(), String> {
+        let (width, height, pixels) = self.frame();
+        save_png(path.as_ref(), width, height, &pixels)
+    }
+
+    // Compares the current frame against a golden png, allowing each channel to be
+    // off by `tolerance`. A missing golden image is an error, set GAMEPAD_GUI_UPDATE_GOLDEN
+    // to write the current frame instead, whether the image is there or not.
+    pub fn compare_frame<P: AsRef<Path>>(&mut self, golden: P, tolerance: u8) -> Result<bool, String> {
+        let golden = golden.as_ref();
+        if std::env::var_os("GAMEPAD_GUI_UPDATE_GOLDEN").is_some() {
+            self.save_frame(golden)?;
+            return Ok(true);
+        }
+        if !golden.exists() {
+            return Err(format!("{}: no such golden image, set GAMEPAD_GUI_UPDATE_GOLDEN to create it", golden.display()));
+        }
+
+        let (width, height, pixels) = self.frame();
+        let (golden_width, golden_height, golden_pixels) = load_png(golden)?;
+        if (width, height) != (golden_width, golden_height) {
+            return Ok(false);
+        }
+
+        Ok(pixels.iter().zip(golden_pixels.iter()).all(|(a, b)| a.abs_diff(*b) <= tolerance))
+    }
+
+    // Runs the toolkit through a list of (delay, step) steps, ticking a frame
+    // at a time in between so that timing dependent stuff like key repeat and
+    // animations behaves like it would with real input. The delays are counted
+    // on a clock of its own, so it doesn't take any longer than the ticks do.
+    // A bare InputAction is pressed and released in the same tick, hold a
+    // direction with Step::Hold and let go of it with a later Step::Release.
+    // Returns every event emitted along the way.
+    pub fn play<S: Into<Step> + Copy>(&mut self, script: &[(Duration, S)]) -> Vec<TkEvent> {
+        let frame = Duration::from_millis(10);
+        let mut now = self.now();
+        let mut out: Vec<TkEvent> = Vec::new();
+        for (delay, step) in script {
+            let until = now + *delay;
+            while now < until {
+                now = std::cmp::min(now + frame, until);
+                self.clock = Some(now);
+                self.tick();
+                out.extend(std::iter::from_fn(|| self.poll_events()));
+            }
+            match (*step).into() {
+                Step::Tap(action) => self.inject(action),
+                Step::Hold(action) => self.press(action),
+                Step::Release(action) => self.release(action),
+            }
+            self.tick();
+            out.extend(std::iter::from_fn(|| self.poll_events()));
+        }
+        // carry on from where the script got to
+        self.clock = None;
+        self.clock_ahead = now.saturating_duration_since(Instant::now());
+        out
+    }
+}
@@ -0,0 +1,85 @@
+use std::path::Path;
+
+use sdl2::{
+    pixels::PixelFormatEnum,
+    render::{BlendMode, Texture, TextureCreator},
+    video::WindowContext,
+};
+
+// Decoded 8 bit RGBA pixels, turned into a texture once it's added to
+// the toolkit. PNGs are decoded in Rust, the `sdl2-image` feature goes
+// through SDL2_image instead, which also does jpg and friends.
+#[derive(Debug, Clone)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Image, String> {
+        let bytes = std::fs::read(path.as_ref()).map_err(|e| format!("{}: {}", path.as_ref().display(), e))?;
+        Image::from_bytes(&bytes).map_err(|e| format!("{}: {}", path.as_ref().display(), e))
+    }
+
+    #[cfg(not(feature = "sdl2-image"))]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Image, String> {
+        let mut decoder = png::Decoder::new(bytes);
+        // palettes, low bit depths and tRNS chunks all end up as plain 8 bit channels
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+        buf.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buf,
+            png::ColorType::Rgb => buf.chunks(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+            png::ColorType::GrayscaleAlpha => buf.chunks(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+            png::ColorType::Grayscale => buf.iter().flat_map(|p| [*p, *p, *p, 255]).collect(),
+            png::ColorType::Indexed => return Err("indexed png wasn't expanded".to_string()),
+        };
+
+        Ok(Image {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    #[cfg(feature = "sdl2-image")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Image, String> {
+        use sdl2::image::ImageRWops;
+
+        let surface = sdl2::rwops::RWops::from_bytes(bytes)?.load()?;
+        let surface = surface.convert_format(PixelFormatEnum::RGBA32)?;
+        let (width, height) = surface.size();
+        // rows can be padded
+        let pitch = surface.pitch() as usize;
+        let pixels = surface.with_lock(|p| {
+            p.chunks(pitch).flat_map(|row| &row[..width as usize * 4]).copied().collect()
+        });
+
+        Ok(Image {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // fails on empty images and ones bigger than the renderer can take
+    pub(crate) fn texture(&self, creator: &TextureCreator<WindowContext>) -> Result<Texture, String> {
+        let mut texture = creator.create_texture_static(PixelFormatEnum::RGBA32, self.width, self.height)
+            .map_err(|e| format!("{}x{} image: {}", self.width, self.height, e))?;
+        texture.update(None, &self.pixels, self.width as usize * 4).map_err(|e| e.to_string())?;
+        texture.set_blend_mode(BlendMode::Blend);
+        Ok(texture)
+    }
+}
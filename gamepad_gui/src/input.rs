@@ -0,0 +1,353 @@
+use std::thread;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use evdev::{
+    Device,
+    InputEventKind,
+    Key,
+    AbsoluteAxisType,
+};
+use inotify::{EventMask, Inotify, WatchMask};
+use sdl2::event::EventSender;
+
+use crate::{InternalTkEvent, InputAction};
+
+fn is_gamepad(dev: &Device) -> bool {
+    let keys = if let Some(keys) = dev.supported_keys() {
+        keys
+    } else {
+        return false;
+    };
+    let has_dpad = keys.contains(Key::BTN_DPAD_UP)
+        || dev.supported_absolute_axes().is_some_and(|abs| abs.contains(AbsoluteAxisType::ABS_HAT0Y));
+
+    keys.contains(Key::BTN_SOUTH) && keys.contains(Key::BTN_EAST) && has_dpad
+}
+
+fn is_rinputer(dev: &Device) -> bool {
+    dev.name().is_some_and(|name| name.to_lowercase().contains("rinputer"))
+}
+
+fn convert_key(key: Key, value: i32, bindings: &HashMap<u16, InputAction>) -> Option<InternalTkEvent> {
+    // 1 is press, 0 is release and 2 is kernel autorepeat, we do our own repeating
+    let pressed = match value {
+        1 => true,
+        0 => false,
+        _ => return None,
+    };
+    bindings.get(&key.code()).and_then(|action| action.to_event(pressed))
+}
+
+fn convert_hat(axis: AbsoluteAxisType, value: i32) -> Option<InternalTkEvent> {
+    match axis {
+        AbsoluteAxisType::ABS_HAT0X => Some(InternalTkEvent::HoldTabPos(value.signum())),
+        AbsoluteAxisType::ABS_HAT0Y => Some(InternalTkEvent::HoldBtnPos(value.signum())),
+        _ => None,
+    }
+}
+
+// turns raw stick positions into -1/0/1, only reporting changes
+struct StickAxis {
+    center: i32,
+    threshold: i32,
+    dir: i32,
+}
+
+impl StickAxis {
+    fn new(min: i32, max: i32, deadzone: f32) -> StickAxis {
+        StickAxis {
+            center: (min + max) / 2,
+            threshold: ((max - min) as f32 / 2.0 * deadzone) as i32,
+            dir: 0,
+        }
+    }
+    fn update(&mut self, value: i32) -> Option<i32> {
+        let offset = value - self.center;
+        // once pushed it has to come back a bit past the deadzone to let go,
+        // otherwise a stick resting right on the edge keeps pressing
+        let release = self.threshold * 3 / 4;
+        let dir = if offset > self.threshold || (self.dir == 1 && offset > release) {
+            1
+        } else if -offset > self.threshold || (self.dir == -1 && -offset > release) {
+            -1
+        } else {
+            0
+        };
+
+        if dir != self.dir {
+            self.dir = dir;
+            Some(dir)
+        } else {
+            None
+        }
+    }
+}
+
+pub(crate) struct DeviceConfig {
+    pub deadzone: f32,
+    pub bindings: HashMap<u16, InputAction>,
+}
+
+fn is_event_node(path: &Path) -> bool {
+    path.file_name().is_some_and(|f| f.to_string_lossy().starts_with("event"))
+}
+
+// open it and convert events into sdl input events:
+fn read_device(mut dev: Device, cfg: Arc<DeviceConfig>, sdl_tx: Arc<EventSender>) {
+    let name = dev.name().unwrap_or("unknown device").to_string();
+    sdl_tx.push_custom_event(InternalTkEvent::GamepadConnected(name.clone())).unwrap();
+
+    let abs = dev.get_abs_state().ok();
+    let stick = |axis: AbsoluteAxisType| abs.map(|abs| {
+        let info = abs[axis.0 as usize];
+        StickAxis::new(info.minimum, info.maximum, cfg.deadzone)
+    });
+    let mut stick_x = stick(AbsoluteAxisType::ABS_X);
+    let mut stick_y = stick(AbsoluteAxisType::ABS_Y);
+
+    loop {
+        let events = match dev.fetch_events() {
+            Ok(events) => events,
+            // ENODEV once the controller goes away
+            Err(_) => break,
+        };
+
+        for ev in events {
+            let out = match ev.kind() {
+                InputEventKind::Key(key) => convert_key(key, ev.value(), &cfg.bindings),
+                InputEventKind::AbsAxis(AbsoluteAxisType::ABS_X) => {
+                    stick_x.as_mut().and_then(|s| s.update(ev.value())).map(InternalTkEvent::HoldTabPos)
+                },
+                InputEventKind::AbsAxis(AbsoluteAxisType::ABS_Y) => {
+                    stick_y.as_mut().and_then(|s| s.update(ev.value())).map(InternalTkEvent::HoldBtnPos)
+                },
+                InputEventKind::AbsAxis(axis) => convert_hat(axis, ev.value()),
+                _ => None,
+            };
+
+            if let Some(tk_ev) = out {
+                sdl_tx.push_custom_event(tk_ev).unwrap();
+            }
+        }
+    }
+
+    // the watcher forgets the node once it's deleted, so a controller
+    // plugged back in on the same node gets opened again
+    sdl_tx.push_custom_event(InternalTkEvent::GamepadDisconnected(name)).unwrap();
+}
+
+fn try_open(path: &Path, cfg: &Arc<DeviceConfig>, opened: &Arc<Mutex<HashSet<PathBuf>>>, sdl_tx: &Arc<EventSender>) {
+    if !is_event_node(path) || opened.lock().unwrap().contains(path) {
+        return;
+    }
+
+    // freshly created nodes are often root-only until udev fixes up
+    // the permissions, we'll get another go at it on IN_ATTRIB
+    let dev = if let Ok(dev) = Device::open(path) {
+        dev
+    } else {
+        return;
+    };
+    if !is_rinputer(&dev) && !is_gamepad(&dev) {
+        return;
+    }
+
+    opened.lock().unwrap().insert(path.to_path_buf());
+    let sdl_tx = sdl_tx.clone();
+    let cfg = cfg.clone();
+    thread::spawn(move || read_device(dev, cfg, sdl_tx));
+}
+
+pub(crate) fn handle_inputs(sdl_tx: EventSender, cfg: DeviceConfig) {
+    // it's much faster to use raw linux evdev apis
+    // than sdl2 gamecontroller api
+    let sdl_tx = Arc::new(sdl_tx);
+    let cfg = Arc::new(cfg);
+    let opened: Arc<Mutex<HashSet<PathBuf>>> = Arc::new(Mutex::new(HashSet::new()));
+
+    // start watching before the initial scan so we don't miss anything in between,
+    // without it the gamepads that are already plugged in still work
+    let inotify = Inotify::init().and_then(|inotify| {
+        inotify.watches().add("/dev/input", WatchMask::CREATE | WatchMask::ATTRIB | WatchMask::DELETE)?;
+        Ok(inotify)
+    });
+    let mut inotify = match inotify {
+        Ok(inotify) => inotify,
+        Err(e) => {
+            eprintln!("gamepad_gui: can't watch /dev/input, gamepads plugged in later won't work: {}", e);
+            scan_devices(&cfg, &opened, &sdl_tx);
+            return;
+        },
+    };
+    scan_devices(&cfg, &opened, &sdl_tx);
+
+    let mut buf = [0u8; 1024];
+    loop {
+        let events = match inotify.read_events_blocking(&mut buf) {
+            Ok(events) => events,
+            Err(e) => {
+                eprintln!("gamepad_gui: lost the watch on /dev/input, gamepads plugged in later won't work: {}", e);
+                return;
+            },
+        };
+        for ev in events {
+            if let Some(name) = ev.name {
+                let path = Path::new("/dev/input").join(name);
+                if ev.mask.contains(EventMask::DELETE) {
+                    opened.lock().unwrap().remove(&path);
+                } else {
+                    try_open(&path, &cfg, &opened, &sdl_tx);
+                }
+            }
+        }
+    }
+}
+
+fn scan_devices(cfg: &Arc<DeviceConfig>, opened: &Arc<Mutex<HashSet<PathBuf>>>, sdl_tx: &Arc<EventSender>) {
+    // find rinputer(or close enough) devices:
+    if let Ok(dir) = std::fs::read_dir("/dev/input") {
+        for entry in dir.flatten() {
+            try_open(&entry.path(), cfg, opened, sdl_tx);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RepeatConfig {
+    pub delay: Duration,
+    pub interval: Duration,
+    pub fastest: Duration,
+}
+
+impl Default for RepeatConfig {
+    fn default() -> RepeatConfig {
+        RepeatConfig {
+            delay: Duration::from_millis(400),
+            interval: Duration::from_millis(150),
+            fastest: Duration::from_millis(40),
+        }
+    }
+}
+
+// keeps firing a held direction, starting slow and speeding up
+#[derive(Debug)]
+pub(crate) struct Repeater {
+    cfg: RepeatConfig,
+    dir: i32,
+    next: Instant,
+    interval: Duration,
+}
+
+impl Repeater {
+    pub fn new(cfg: RepeatConfig) -> Repeater {
+        Repeater {
+            cfg,
+            dir: 0,
+            next: Instant::now(),
+            interval: cfg.interval,
+        }
+    }
+    // returns true if the direction was just pressed and should fire right away
+    pub fn hold(&mut self, dir: i32, now: Instant) -> bool {
+        if dir == self.dir {
+            return false;
+        }
+        self.dir = dir;
+        self.next = now + self.cfg.delay;
+        self.interval = self.cfg.interval;
+        dir != 0
+    }
+    pub fn poll(&mut self, now: Instant) -> Option<i32> {
+        if self.dir == 0 || now < self.next {
+            return None;
+        }
+        self.next = now + self.interval;
+        self.interval = std::cmp::max(self.interval * 3 / 4, self.cfg.fastest);
+        Some(self.dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repeat() -> RepeatConfig {
+        RepeatConfig {
+            delay: Duration::from_millis(400),
+            interval: Duration::from_millis(128),
+            fastest: Duration::from_millis(50),
+        }
+    }
+
+    #[test]
+    fn repeater_fires_once_then_waits_for_the_delay() {
+        let start = Instant::now();
+        let mut rep = Repeater::new(repeat());
+        assert!(rep.hold(1, start));
+        assert_eq!(rep.poll(start), None);
+        assert_eq!(rep.poll(start + Duration::from_millis(399)), None);
+        assert_eq!(rep.poll(start + Duration::from_millis(400)), Some(1));
+        assert_eq!(rep.poll(start + Duration::from_millis(527)), None);
+        assert_eq!(rep.poll(start + Duration::from_millis(528)), Some(1));
+    }
+
+    #[test]
+    fn repeater_speeds_up_to_fastest() {
+        let start = Instant::now();
+        let mut rep = Repeater::new(repeat());
+        rep.hold(-1, start);
+        let mut now = start + Duration::from_millis(400);
+        let mut gaps = Vec::new();
+        let mut last = now;
+        while gaps.len() < 6 {
+            if rep.poll(now) == Some(-1) {
+                gaps.push((now - last).as_millis());
+                last = now;
+            }
+            now += Duration::from_millis(1);
+        }
+        // 3/4 of the last one each time until it's down to 50
+        assert_eq!(gaps, vec![0, 128, 96, 72, 54, 50]);
+    }
+
+    #[test]
+    fn repeater_stops_on_release_and_restarts_on_a_new_direction() {
+        let start = Instant::now();
+        let mut rep = Repeater::new(repeat());
+        rep.hold(1, start);
+        assert!(!rep.hold(1, start + Duration::from_millis(10)));
+        assert!(!rep.hold(0, start + Duration::from_millis(20)));
+        assert_eq!(rep.poll(start + Duration::from_secs(1)), None);
+
+        let later = start + Duration::from_secs(2);
+        assert!(rep.hold(-1, later));
+        assert_eq!(rep.poll(later + Duration::from_millis(399)), None);
+        assert_eq!(rep.poll(later + Duration::from_millis(400)), Some(-1));
+    }
+
+    #[test]
+    fn stick_ignores_the_deadzone_and_only_reports_changes() {
+        // center 0, pushed past 50
+        let mut stick = StickAxis::new(-100, 100, 0.5);
+        assert_eq!(stick.update(10), None);
+        assert_eq!(stick.update(-50), None);
+        assert_eq!(stick.update(60), Some(1));
+        assert_eq!(stick.update(90), None);
+        assert_eq!(stick.update(-60), Some(-1));
+        assert_eq!(stick.update(0), Some(0));
+    }
+
+    #[test]
+    fn stick_has_to_come_back_past_the_deadzone_to_let_go() {
+        let mut stick = StickAxis::new(0, 255, 0.5);
+        assert_eq!(stick.center, 127);
+        assert_eq!(stick.update(127 + 64), Some(1));
+        // inside the deadzone but not far enough in to let go
+        assert_eq!(stick.update(127 + 50), None);
+        assert_eq!(stick.update(127 + 40), Some(0));
+        assert_eq!(stick.update(127 - 50), None);
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use sdl2::{
+    keyboard::Keycode,
+    controller::Button,
+};
+
+use crate::InternalTkEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Press,
+    Back,
+    PageUp,
+    PageDown,
+    // L1/R1, unlike left/right these always switch tabs
+    PrevTab,
+    NextTab,
+    Quit,
+}
+
+impl InputAction {
+    pub fn from_name(name: &str) -> Option<InputAction> {
+        match name.to_lowercase().as_str() {
+            "up" =>         Some(InputAction::Up),
+            "down" =>       Some(InputAction::Down),
+            "left" =>       Some(InputAction::Left),
+            "right" =>      Some(InputAction::Right),
+            "press" =>      Some(InputAction::Press),
+            "back" =>       Some(InputAction::Back),
+            "page_up" =>    Some(InputAction::PageUp),
+            "page_down" =>  Some(InputAction::PageDown),
+            "prev_tab" =>   Some(InputAction::PrevTab),
+            "next_tab" =>   Some(InputAction::NextTab),
+            "quit" =>       Some(InputAction::Quit),
+            _ => None,
+        }
+    }
+
+    // directions are held and repeated by the toolkit,
+    // everything else only fires when pressed
+    pub(crate) fn to_event(self, pressed: bool) -> Option<InternalTkEvent> {
+        let held = if pressed { 1 } else { 0 };
+        match self {
+            InputAction::Up =>      Some(InternalTkEvent::HoldBtnPos(-held)),
+            InputAction::Down =>    Some(InternalTkEvent::HoldBtnPos(held)),
+            InputAction::Left =>    Some(InternalTkEvent::HoldTabPos(-held)),
+            InputAction::Right =>   Some(InternalTkEvent::HoldTabPos(held)),
+            _ if !pressed => None,
+            InputAction::Press =>   Some(InternalTkEvent::Press),
+            InputAction::Back =>    Some(InternalTkEvent::Back),
+            InputAction::PageUp =>  Some(InternalTkEvent::ChangePage(-1)),
+            InputAction::PageDown => Some(InternalTkEvent::ChangePage(1)),
+            InputAction::PrevTab => Some(InternalTkEvent::SwitchTab(-1)),
+            InputAction::NextTab => Some(InternalTkEvent::SwitchTab(1)),
+            InputAction::Quit =>    Some(InternalTkEvent::Quit),
+        }
+    }
+}
+
+// Bindings from keyboard keys, sdl2 game controller buttons and raw evdev
+// key codes to toolkit actions.
+//
+// Can be loaded from a toml file, where each section maps an input name to
+// an action name:
+//
+// [keyboard]
+// Up = "up"
+// Return = "press"
+//
+// [controller]
+// a = "press"
+//
+// [evdev]
+// BTN_SOUTH = "press"
+// 0x131 = "back"
+//
+// Controller bindings are empty by default, gamepads are read through evdev.
+// Only bind them if there's no evdev access, otherwise every press counts twice.
+#[derive(Debug, Clone)]
+pub struct InputMap {
+    pub(crate) keyboard: HashMap<Keycode, InputAction>,
+    pub(crate) controller: HashMap<Button, InputAction>,
+    pub(crate) evdev: HashMap<u16, InputAction>,
+}
+
+impl Default for InputMap {
+    fn default() -> InputMap {
+        InputMap::new()
+            .bind_key(Keycode::Up, InputAction::Up)
+            .bind_key(Keycode::Down, InputAction::Down)
+            .bind_key(Keycode::Left, InputAction::Left)
+            .bind_key(Keycode::Right, InputAction::Right)
+            .bind_key(Keycode::Return, InputAction::Press)
+            .bind_key(Keycode::Backspace, InputAction::Back)
+            .bind_key(Keycode::PageUp, InputAction::PageUp)
+            .bind_key(Keycode::PageDown, InputAction::PageDown)
+            .bind_key(Keycode::Escape, InputAction::Back)
+            .bind_evdev(evdev::Key::BTN_DPAD_UP.code(), InputAction::Up)
+            .bind_evdev(evdev::Key::BTN_DPAD_DOWN.code(), InputAction::Down)
+            .bind_evdev(evdev::Key::BTN_DPAD_LEFT.code(), InputAction::Left)
+            .bind_evdev(evdev::Key::BTN_DPAD_RIGHT.code(), InputAction::Right)
+            .bind_evdev(evdev::Key::BTN_TL.code(), InputAction::PrevTab)
+            .bind_evdev(evdev::Key::BTN_TR.code(), InputAction::NextTab)
+            .bind_evdev(evdev::Key::BTN_TL2.code(), InputAction::PageUp)
+            .bind_evdev(evdev::Key::BTN_TR2.code(), InputAction::PageDown)
+            .bind_evdev(evdev::Key::BTN_SOUTH.code(), InputAction::Press)
+            .bind_evdev(evdev::Key::BTN_EAST.code(), InputAction::Back)
+    }
+}
+
+impl InputMap {
+    // no bindings at all
+    pub fn new() -> InputMap {
+        InputMap {
+            keyboard: HashMap::new(),
+            controller: HashMap::new(),
+            evdev: HashMap::new(),
+        }
+    }
+    pub fn bind_key(mut self, key: Keycode, action: InputAction) -> InputMap {
+        self.keyboard.insert(key, action);
+        self
+    }
+    pub fn bind_button(mut self, button: Button, action: InputAction) -> InputMap {
+        self.controller.insert(button, action);
+        self
+    }
+    pub fn bind_evdev(mut self, code: u16, action: InputAction) -> InputMap {
+        self.evdev.insert(code, action);
+        self
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<InputMap, String> {
+        let contents = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| format!("{}: {}", path.as_ref().display(), e))?;
+        InputMap::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<InputMap, String> {
+        let root = toml::Value::from_str(contents).map_err(|e| e.to_string())?;
+        let mut map = InputMap::new();
+
+        for (section, table) in root.as_table().unwrap() {
+            let table = table.as_table().ok_or(format!("[{}] is not a section", section))?;
+            for (input, action) in table {
+                let action = action.as_str()
+                    .and_then(InputAction::from_name)
+                    .ok_or(format!("[{}] {}: unknown action {}", section, input, action))?;
+
+                match section.as_str() {
+                    "keyboard" => {
+                        let key = Keycode::from_name(input).ok_or(format!("unknown key {}", input))?;
+                        map.keyboard.insert(key, action);
+                    },
+                    "controller" => {
+                        let button = Button::from_string(input).ok_or(format!("unknown controller button {}", input))?;
+                        map.controller.insert(button, action);
+                    },
+                    "evdev" => {
+                        let code = if let Some(hex) = input.strip_prefix("0x") {
+                            u16::from_str_radix(hex, 16).ok()
+                        } else if let Ok(key) = evdev::Key::from_str(input) {
+                            Some(key.code())
+                        } else {
+                            input.parse::<u16>().ok()
+                        };
+                        map.evdev.insert(code.ok_or(format!("unknown evdev code {}", input))?, action);
+                    },
+                    _ => return Err(format!("unknown section [{}]", section)),
+                }
+            }
+        }
+
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_section() {
+        let map = InputMap::parse(r#"
+            [keyboard]
+            Up = "up"
+            Return = "PRESS"
+
+            [controller]
+            a = "press"
+
+            [evdev]
+            BTN_SOUTH = "press"
+            0x131 = "back"
+            312 = "page_up"
+        "#).unwrap();
+
+        assert_eq!(map.keyboard.get(&Keycode::Up), Some(&InputAction::Up));
+        assert_eq!(map.keyboard.get(&Keycode::Return), Some(&InputAction::Press));
+        assert_eq!(map.controller.get(&Button::A), Some(&InputAction::Press));
+        assert_eq!(map.evdev.get(&evdev::Key::BTN_SOUTH.code()), Some(&InputAction::Press));
+        assert_eq!(map.evdev.get(&0x131), Some(&InputAction::Back));
+        assert_eq!(map.evdev.get(&312), Some(&InputAction::PageUp));
+    }
+
+    #[test]
+    fn starts_from_nothing() {
+        let map = InputMap::parse("[keyboard]\nUp = \"down\"\n").unwrap();
+        assert_eq!(map.keyboard.len(), 1);
+        assert!(map.controller.is_empty());
+        assert!(map.evdev.is_empty());
+    }
+
+    #[test]
+    fn reports_what_is_wrong() {
+        let err = |contents: &str| InputMap::parse(contents).unwrap_err();
+        assert_eq!(err("[keyboard]\nUp = \"jump\"\n"), "[keyboard] Up: unknown action \"jump\"");
+        assert_eq!(err("[keyboard]\nUp = 3\n"), "[keyboard] Up: unknown action 3");
+        assert_eq!(err("[mouse]\nleft = \"press\"\n"), "unknown section [mouse]");
+        assert_eq!(err("keyboard = \"up\"\n"), "[keyboard] is not a section");
+        assert_eq!(err("[keyboard]\nNotAKey = \"up\"\n"), "unknown key NotAKey");
+        assert_eq!(err("[controller]\nz = \"up\"\n"), "unknown controller button z");
+        assert_eq!(err("[evdev]\n0xzz = \"up\"\n"), "unknown evdev code 0xzz");
+        assert_eq!(err("[evdev]\n70000 = \"up\"\n"), "unknown evdev code 70000");
+        assert!(InputMap::parse("[keyboard").is_err());
+    }
+
+    #[test]
+    fn directions_are_held_and_the_rest_fire_on_press() {
+        assert_eq!(InputAction::Up.to_event(true), Some(InternalTkEvent::HoldBtnPos(-1)));
+        assert_eq!(InputAction::Up.to_event(false), Some(InternalTkEvent::HoldBtnPos(0)));
+        assert_eq!(InputAction::Right.to_event(true), Some(InternalTkEvent::HoldTabPos(1)));
+        assert_eq!(InputAction::Press.to_event(true), Some(InternalTkEvent::Press));
+        assert_eq!(InputAction::Press.to_event(false), None);
+        assert_eq!(InputAction::NextTab.to_event(true), Some(InternalTkEvent::SwitchTab(1)));
+        assert_eq!(InputAction::PageUp.to_event(true), Some(InternalTkEvent::ChangePage(-1)));
+    }
+
+    #[test]
+    fn action_names_ignore_case() {
+        assert_eq!(InputAction::from_name("Page_Down"), Some(InputAction::PageDown));
+        assert_eq!(InputAction::from_name("prev_tab"), Some(InputAction::PrevTab));
+        assert_eq!(InputAction::from_name("jump"), None);
+    }
+}
@@ -0,0 +1,337 @@
+use sdl2::{
+    rect::{Rect, Point},
+    render::{Canvas, Texture},
+    video::Window,
+};
+
+use crate::{InternalTkEvent, Theme, ui_scale, destroy};
+use crate::font::TextRenderer;
+
+// every character row is 10 keys wide so moving up and down stays in the same column
+const LAYERS: [[&str; 4]; 3] = [
+    ["1234567890", "qwertyuiop", "asdfghjkl;", "zxcvbnm,./"],
+    ["!@#$%^&*()", "QWERTYUIOP", "ASDFGHJKL:", "ZXCVBNM<>?"],
+    ["1234567890", "!@#$%^&*()", "`~-_=+[]{}", "\\|;:'\",.<>"],
+];
+const LOWER: usize = 0;
+const UPPER: usize = 1;
+const SYMBOLS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Key {
+    Char(char),
+    Shift,
+    Symbols,
+    Space,
+    Backspace,
+    Confirm,
+    Cancel,
+}
+
+// the bottom row is the same on every layer
+const SPECIAL: [Key; 6] = [Key::Shift, Key::Symbols, Key::Space, Key::Backspace, Key::Confirm, Key::Cancel];
+const ROWS: usize = 5;
+
+fn row_len(row: usize) -> usize {
+    if row < 4 { 10 } else { SPECIAL.len() }
+}
+
+fn key_at(layer: usize, row: usize, col: usize) -> Key {
+    if row < 4 {
+        Key::Char(LAYERS[layer][row].chars().nth(col).unwrap())
+    } else {
+        SPECIAL[col]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum KeyboardState {
+    Open,
+    Confirmed,
+    Cancelled,
+}
+
+// On-screen keyboard drawn over the bottom of the screen, navigated with
+// the d-pad. Press types the selected key, Back cancels.
+pub(crate) struct Keyboard {
+    buffer: String,
+    layer: usize,
+    row: usize,
+    col: usize,
+    open: bool,
+    // key labels for (layer, scale)
+    labels: Option<(usize, f32, Vec<Vec<Texture>>)>,
+    // the edit line for (text, scale), rendered again when either changes
+    edit: Option<(String, f32, Texture)>,
+    // where each key ended up on the last draw, for touch input
+    key_rects: Vec<(usize, usize, Rect)>,
+}
+
+impl Keyboard {
+    pub fn new() -> Keyboard {
+        Keyboard {
+            buffer: String::new(),
+            layer: LOWER,
+            row: 1,
+            col: 0,
+            open: false,
+            labels: None,
+            edit: None,
+            key_rects: Vec::new(),
+        }
+    }
+    pub fn open(&mut self, value: &str) {
+        self.buffer = value.to_string();
+        self.layer = LOWER;
+        self.row = 1;
+        self.col = 0;
+        self.open = true;
+    }
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+    pub fn buffer(&self) -> &str {
+        &self.buffer
+    }
+
+    // keeps roughly the same horizontal position between rows of different widths
+    fn move_row(&mut self, d: i32) {
+        let old_len = row_len(self.row);
+        self.row = (self.row as i32 + d).rem_euclid(ROWS as i32) as usize;
+        self.col = (self.col * 2 + 1) * row_len(self.row) / (old_len * 2);
+    }
+    fn move_col(&mut self, d: i32) {
+        self.col = (self.col as i32 + d).rem_euclid(row_len(self.row) as i32) as usize;
+    }
+
+    fn type_key(&mut self, key: Key) -> KeyboardState {
+        match key {
+            Key::Char(c) => {
+                self.buffer.push(c);
+                // shift only lasts for one character
+                if self.layer == UPPER {
+                    self.layer = LOWER;
+                }
+            },
+            Key::Shift => self.layer = if self.layer == UPPER { LOWER } else { UPPER },
+            Key::Symbols => self.layer = if self.layer == SYMBOLS { LOWER } else { SYMBOLS },
+            Key::Space => self.buffer.push(' '),
+            Key::Backspace => { self.buffer.pop(); },
+            Key::Confirm => {
+                self.open = false;
+                return KeyboardState::Confirmed;
+            },
+            Key::Cancel => {
+                self.open = false;
+                return KeyboardState::Cancelled;
+            },
+        }
+        KeyboardState::Open
+    }
+
+    pub fn handle(&mut self, ev: &InternalTkEvent) -> KeyboardState {
+        match ev {
+            InternalTkEvent::ChangeBtnPos(d) => self.move_row(d.signum()),
+            InternalTkEvent::ChangeTabPos(d) => self.move_col(d.signum()),
+            InternalTkEvent::Press => return self.type_key(key_at(self.layer, self.row, self.col)),
+            InternalTkEvent::Back => return self.type_key(Key::Cancel),
+            InternalTkEvent::TouchPress(x, y) => {
+                let hit = self.key_rects.iter()
+                    .find(|(_, _, rect)| rect.contains_point(Point::new(*x, *y)))
+                    .map(|(row, col, _)| (*row, *col));
+                if let Some((row, col)) = hit {
+                    self.row = row;
+                    self.col = col;
+                    return self.type_key(key_at(self.layer, row, col));
+                }
+            },
+            _ => (),
+        }
+        KeyboardState::Open
+    }
+
+    fn label(&self, key: Key) -> String {
+        match key {
+            Key::Char(c) => c.to_string(),
+            Key::Shift => "Shift".to_string(),
+            Key::Symbols if self.layer == SYMBOLS => "abc".to_string(),
+            Key::Symbols => "Sym".to_string(),
+            Key::Space => "Space".to_string(),
+            Key::Backspace => "Del".to_string(),
+            Key::Confirm => "OK".to_string(),
+            Key::Cancel => "Cancel".to_string(),
+        }
+    }
+
+    pub fn draw(&mut self, canvas: &mut Canvas<Window>, theme: &Theme, text: &mut TextRenderer, masked: bool) {
+        let scale = ui_scale(canvas);
+        if !self.labels.as_ref().is_some_and(|(layer, s, _)| *layer == self.layer && *s == scale) {
+            let labels = (0..ROWS)
+                .map(|row| (0..row_len(row)).map(|col| text.render(&self.label(key_at(self.layer, row, col)), scale)).collect())
+                .collect();
+            if let Some((_, _, old)) = self.labels.replace((self.layer, scale, labels)) {
+                old.into_iter().flatten().for_each(|label| destroy(Some(label)));
+            }
+        }
+
+        let shown = if masked {
+            "*".repeat(self.buffer.chars().count())
+        } else {
+            self.buffer.clone()
+        };
+        let line = format!("{}_", shown);
+        if !self.edit.as_ref().is_some_and(|(l, s, _)| *l == line && *s == scale) {
+            let edit = text.render(&line, scale);
+            if let Some((_, _, old)) = self.edit.replace((line, scale, edit)) {
+                destroy(Some(old));
+            }
+        }
+        let (_, _, edit) = self.edit.as_mut().unwrap();
+        theme.tint(edit, false);
+
+        let (width, height) = canvas.output_size().unwrap();
+        let padding = theme.padding as i32;
+        let key_height = edit.query().height as i32 + padding * 2;
+        // the edit line plus one line per row of keys
+        let top = height as i32 - key_height * (ROWS as i32 + 1);
+
+        canvas.set_draw_color(theme.background);
+        canvas.fill_rect(Rect::new(0, top, width, (height as i32 - top) as u32)).unwrap();
+        canvas.set_draw_color(theme.foreground);
+        canvas.draw_line((0, top), (width as i32, top)).unwrap();
+
+        let attr = edit.query();
+        canvas.copy(edit, None, Rect::new(padding, top + padding, attr.width, attr.height)).unwrap();
+
+        self.key_rects.clear();
+        let layer = self.layer;
+        let (_, _, labels) = self.labels.as_mut().unwrap();
+        for (row, keys) in labels.iter_mut().enumerate() {
+            let key_width = width as i32 / keys.len() as i32;
+            let y = top + key_height * (row as i32 + 1);
+            for (col, label) in keys.iter_mut().enumerate() {
+                let rect = Rect::new(key_width * col as i32, y, key_width as u32, key_height as u32);
+                let selected = row == self.row && col == self.col;
+                // the active layer's key stays lit
+                let active = match key_at(layer, row, col) {
+                    Key::Shift => layer == UPPER,
+                    Key::Symbols => layer == SYMBOLS,
+                    _ => false,
+                };
+                theme.tint(label, selected || active);
+                if selected {
+                    canvas.set_draw_color(theme.accent);
+                    canvas.draw_rect(rect).unwrap();
+                }
+                let attr = label.query();
+                let dst = Rect::from_center(rect.center(), attr.width, attr.height);
+                canvas.copy(label, None, dst).unwrap();
+                self.key_rects.push((row, col, rect));
+            }
+        }
+    }
+}
+
+impl Drop for Keyboard {
+    fn drop(&mut self) {
+        if let Some((_, _, labels)) = self.labels.take() {
+            labels.into_iter().flatten().for_each(|label| destroy(Some(label)));
+        }
+        destroy(self.edit.take().map(|(_, _, edit)| edit));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyboard(value: &str) -> Keyboard {
+        let mut kb = Keyboard::new();
+        kb.open(value);
+        kb
+    }
+
+    fn press_at(kb: &mut Keyboard, row: usize, col: usize) -> KeyboardState {
+        kb.row = row;
+        kb.col = col;
+        kb.handle(&InternalTkEvent::Press)
+    }
+
+    #[test]
+    fn shift_lasts_for_one_character() {
+        let mut kb = keyboard("");
+        // q, Shift, q, q
+        press_at(&mut kb, 1, 0);
+        press_at(&mut kb, 4, 0);
+        press_at(&mut kb, 1, 0);
+        press_at(&mut kb, 1, 0);
+        assert_eq!(kb.buffer(), "qQq");
+    }
+
+    #[test]
+    fn symbols_stay_until_switched_back() {
+        let mut kb = keyboard("");
+        press_at(&mut kb, 4, 1);
+        press_at(&mut kb, 2, 0);
+        press_at(&mut kb, 2, 1);
+        press_at(&mut kb, 4, 1);
+        press_at(&mut kb, 2, 0);
+        assert_eq!(kb.buffer(), "`~a");
+    }
+
+    #[test]
+    fn moving_onto_a_shorter_row_keeps_the_position() {
+        let mut kb = keyboard("");
+        kb.row = 3;
+        kb.col = 9;
+        kb.handle(&InternalTkEvent::ChangeBtnPos(1));
+        assert_eq!((kb.row, kb.col), (4, 5));
+        kb.handle(&InternalTkEvent::ChangeBtnPos(-1));
+        assert_eq!((kb.row, kb.col), (3, 9));
+
+        kb.col = 0;
+        kb.handle(&InternalTkEvent::ChangeBtnPos(1));
+        assert_eq!((kb.row, kb.col), (4, 0));
+    }
+
+    #[test]
+    fn rows_and_columns_wrap() {
+        let mut kb = keyboard("");
+        kb.row = 0;
+        kb.col = 0;
+        kb.handle(&InternalTkEvent::ChangeBtnPos(-1));
+        assert_eq!((kb.row, kb.col), (4, 0));
+        kb.handle(&InternalTkEvent::ChangeTabPos(-1));
+        assert_eq!((kb.row, kb.col), (4, 5));
+        kb.handle(&InternalTkEvent::ChangeBtnPos(1));
+        assert_eq!((kb.row, kb.col), (0, 9));
+        kb.handle(&InternalTkEvent::ChangeTabPos(1));
+        assert_eq!((kb.row, kb.col), (0, 0));
+    }
+
+    #[test]
+    fn backspace_on_an_empty_buffer_does_nothing() {
+        let mut kb = keyboard("a");
+        assert_eq!(press_at(&mut kb, 4, 3), KeyboardState::Open);
+        assert_eq!(press_at(&mut kb, 4, 3), KeyboardState::Open);
+        assert_eq!(kb.buffer(), "");
+        assert!(kb.is_open());
+    }
+
+    #[test]
+    fn ok_and_cancel_close_the_keyboard() {
+        let mut kb = keyboard("value");
+        press_at(&mut kb, 4, 2);
+        assert_eq!(press_at(&mut kb, 4, 4), KeyboardState::Confirmed);
+        assert!(!kb.is_open());
+        assert_eq!(kb.buffer(), "value ");
+
+        let mut kb = keyboard("value");
+        assert_eq!(press_at(&mut kb, 4, 5), KeyboardState::Cancelled);
+        assert!(!kb.is_open());
+
+        let mut kb = keyboard("value");
+        assert_eq!(kb.handle(&InternalTkEvent::Back), KeyboardState::Cancelled);
+        assert!(!kb.is_open());
+    }
+}
@@ -1,6 +1,11 @@
 use std::thread;
+use std::env;
 use std::num::Wrapping;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::borrow::Cow;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
 use derivative::Derivative;
 
 use sdl2::{
@@ -8,30 +13,111 @@ use sdl2::{
         Rect,
         Point,
     },
-    render::{Canvas, Texture},
-    pixels::Color,
-    event::{Event, EventSender},
-    keyboard::Keycode,
+    render::{Canvas, Texture, TextureCreator},
+    event::{Event, EventSender, WindowEvent},
+    controller::GameController,
     mouse::MouseButton,
+    video::{FullscreenType, WindowContext, WindowPos},
+    GameControllerSubsystem,
 };
 
 #[inline]
 fn remap(x: i32, min: i32, max: i32, outmin: i32, outmax: i32) -> i32 {
+    if min == max {
+        return outmin;
+    }
     (Wrapping(x - min) * Wrapping(outmax - outmin) / Wrapping(max - min) + Wrapping(outmin)).0
 }
 
+// everything was laid out for 640x480 originally
+fn ui_scale(canvas: &Canvas<sdl2::video::Window>) -> f32 {
+    let (w, h) = canvas.output_size().unwrap();
+    std::cmp::min(w, h) as f32 / 480.0
+}
+
+// largest rect with the texture's aspect ratio that fits in the middle of `into`
+fn fit(texture: &Texture, into: Rect) -> Rect {
+    let attr = texture.query();
+    let scale = f32::min(into.width() as f32 / attr.width as f32, into.height() as f32 / attr.height as f32);
+    Rect::from_center(into.center(), (attr.width as f32 * scale) as u32, (attr.height as f32 * scale) as u32)
+}
+
+// sdl2 is built with unsafe_textures, so textures stay around until they're
+// destroyed by hand, and that has to happen while the renderer's still there
+fn destroy(texture: Option<Texture>) {
+    if let Some(texture) = texture {
+        unsafe { texture.destroy() }
+    }
+}
+
 fn clamp<T: std::cmp::PartialOrd>(x: T, min: T, max: T) -> T {
     if x < min { return min }
     if x > max { return max }
     return x
 }
 
+// a list of widgets, either a tab's or a submenu's
+type Page = Vec<Box<dyn Buttonish>>;
+
 trait Buttonish {
-    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, selected: bool, offset_y: i32);
+    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, theme: &Theme, text: &mut TextRenderer, selected: bool, offset_y: i32);
+    // drawn over everything else while the widget is selected
+    fn draw_overlay(&mut self, _: &mut Canvas<sdl2::video::Window>, _: &Theme, _: &mut TextRenderer) {}
     fn captures_input(&self) -> bool;
-    fn action(&mut self, ev: &InternalTkEvent) -> Option<TkEvent>;
+    // labels and separators are skipped over and can't be pressed
+    fn focusable(&self) -> bool { true }
+    // paragraphs get their label wrapped to the window width
+    fn wraps(&self) -> bool { false }
+    // separators, grids and custom widgets don't show a label
+    fn has_label(&self) -> bool { true }
+    // checked after a captured action emits an event,
+    // false lets go of input without returning None
+    fn holds_input(&self) -> bool { true }
+    fn action(&mut self, tab: &Id, ev: &InternalTkEvent) -> Option<TkEvent>;
     fn name(&self) -> &str;
+    fn id(&self) -> &Id;
+    fn set_id(&mut self, id: Id);
     fn rect(&self) -> Rect;
+    fn set_label(&mut self, name: Cow<'static, str>, text: Texture, padding: u32);
+    fn set_y(&mut self, y: i32);
+    // only toggles have a state and only sliders have a level,
+    // false if it doesn't apply to this widget
+    fn set_state(&mut self, _: bool) -> bool { false }
+    fn set_level(&mut self, _: i32, _: i32, _: i32) -> bool { false }
+    fn set_text(&mut self, _: &str) -> bool { false }
+    fn set_choice(&mut self, _: usize) -> bool { false }
+    // grids and rows move their own selection, false once it'd leave them,
+    // `enter` picks the row that's closest to where the selection came from
+    fn navigate(&mut self, _: i32, _: i32) -> bool { false }
+    // left/right never leaves a grid or row, it'd switch tabs
+    fn is_grid(&self) -> bool { false }
+    fn enter(&mut self, _: i32) {}
+    fn select_at(&mut self, _: i32, _: i32) -> bool { false }
+    // the selected widget inside a grid or row, relative to the tab like rect()
+    fn focus_id(&self) -> &Id { self.id() }
+    fn focus_rect(&self) -> Rect { self.rect() }
+    // submenus have a page, it's moved into the tab while open,
+    // grids and rows hand out their widgets so they can be found by id
+    fn page(&self) -> Option<&[Box<dyn Buttonish>]> { None }
+    fn page_mut(&mut self) -> Option<&mut Page> { None }
+    fn take_page(&mut self) -> Option<Page> { None }
+    fn restore_page(&mut self, _: Page) {}
+    // only buttons have room for an icon
+    fn set_icon(&mut self, _: Texture, _: u32) -> bool { false }
+    // only custom widgets can change size on their own
+    fn measure(&mut self, _: &mut DrawContext) -> bool { false }
+    // renders everything again after the window and with it the text scale changed
+    fn rescale(&mut self, ctx: &mut DrawContext) { relabel(self, ctx) }
+}
+
+fn relabel<B: Buttonish + ?Sized>(btn: &mut B, ctx: &mut DrawContext) {
+    if !btn.has_label() {
+        return;
+    }
+    let name = Cow::Owned(btn.name().to_string());
+    let text = if btn.wraps() { ctx.render_wrapped(&name) } else { ctx.render_text(&name) };
+    let padding = ctx.theme().padding;
+    btn.set_label(name, text, padding);
 }
 
 impl core::fmt::Debug for dyn Buttonish {
@@ -39,6 +125,7 @@ impl core::fmt::Debug for dyn Buttonish {
         f.debug_struct("dyn Buttonish")
             .field("captures_input", &self.captures_input())
             .field("name", &self.name())
+            .field("id", self.id())
             .finish()
     }
 }
@@ -46,7 +133,8 @@ impl core::fmt::Debug for dyn Buttonish {
 #[derive(Derivative)]
 #[derivative(Debug)]
 struct Slider {
-    name: &'static str,
+    name: Cow<'static, str>,
+    id: Id,
     #[derivative(Debug="ignore")]
     text: Option<Texture>,
     rect: Option<Rect>,
@@ -58,28 +146,32 @@ struct Slider {
 }
 
 impl Slider {
-    fn new(name: &'static str, text: Texture, line: usize, init: i32, min: i32, max: i32) -> Slider {
+    fn new(name: Cow<'static, str>, text: Texture, line: usize, padding: u32, init: i32, min: i32, max: i32) -> Slider {
+        let (min, max) = (std::cmp::min(min, max), std::cmp::max(min, max));
         let attr = text.query();
         let rect = Rect::new(0, (line as u32 * attr.height) as i32, attr.width, attr.height);
         Slider {
             name,
+            id: Id::unique(),
             text: Some(text),
             rect: Some(rect),
-            outline_rect: Rect::new((attr.width + 5) as i32, (line as u32 * attr.height) as i32, attr.height*5, attr.height),
-            level: init,
+            outline_rect: Rect::new((attr.width + padding) as i32, (line as u32 * attr.height) as i32, attr.height*5, attr.height),
+            level: clamp(init, min, max),
             min, max,
             grabbed: false,
         }
     }
 }
 
+impl Drop for Slider {
+    fn drop(&mut self) {
+        destroy(self.text.take());
+    }
+}
+
 impl Buttonish for Slider {
-    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, selected: bool, offset_y: i32) {
-        if selected {
-            self.text.as_mut().unwrap().set_color_mod(255, 0, 0);
-        } else {
-            self.text.as_mut().unwrap().set_color_mod(255, 255, 255);
-        }
+    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, theme: &Theme, _: &mut TextRenderer, selected: bool, offset_y: i32) {
+        theme.tint(self.text.as_mut().unwrap(), selected);
 
         let mut rect = self.rect.unwrap().clone();
         rect.set_y(rect.y() + offset_y);
@@ -88,9 +180,9 @@ impl Buttonish for Slider {
 
         self.outline_rect.set_y(rect.y());
         if self.grabbed {
-            canvas.set_draw_color(Color::RGB(255, 0, 0));
+            canvas.set_draw_color(theme.accent);
         } else {
-            canvas.set_draw_color(Color::RGB(255, 255, 255));
+            canvas.set_draw_color(theme.foreground);
         }
         canvas.draw_rect(self.outline_rect);
 
@@ -102,33 +194,56 @@ impl Buttonish for Slider {
         }
     }
     fn captures_input(&self) -> bool { true }
-    fn action(&mut self, ev: &InternalTkEvent) -> Option<TkEvent> {
+    fn action(&mut self, tab: &Id, ev: &InternalTkEvent) -> Option<TkEvent> {
         self.grabbed = true;
         return match ev {
             InternalTkEvent::ChangeTabPos(d) => {
                 let new = clamp(self.level + d, self.min, self.max);
                 if new != self.level {
                     self.level = new;
-                    Some(TkEvent::SliderChange(self.name().to_string(), self.level, self.min, self.max))
+                    Some(TkEvent::SliderChange(tab.clone(), self.id.clone(), self.level, self.min, self.max))
                 } else {
                     Some(TkEvent::None)
                 }
             },
-            InternalTkEvent::Press => {
+            InternalTkEvent::Press | InternalTkEvent::Back => {
                 self.grabbed = false;
                 None
             },
             _ => Some(TkEvent::None),
         }
     }
-    fn name(&self) -> &str { self.name }
+    fn name(&self) -> &str { &self.name }
+    fn id(&self) -> &Id { &self.id }
+    fn set_id(&mut self, id: Id) { self.id = id }
     fn rect(&self) -> Rect { self.rect.unwrap() }
+    fn set_label(&mut self, name: Cow<'static, str>, text: Texture, padding: u32) {
+        let attr = text.query();
+        let y = self.rect.unwrap().y();
+        self.rect = Some(Rect::new(0, y, attr.width, attr.height));
+        self.outline_rect = Rect::new((attr.width + padding) as i32, self.outline_rect.y(), attr.height*5, attr.height);
+        self.name = name;
+        destroy(self.text.replace(text));
+    }
+    fn set_y(&mut self, y: i32) { self.rect.as_mut().unwrap().set_y(y) }
+    // the ends can come in either order, but there has to be room to move
+    fn set_level(&mut self, level: i32, min: i32, max: i32) -> bool {
+        if min == max {
+            return false;
+        }
+        let (min, max) = (std::cmp::min(min, max), std::cmp::max(min, max));
+        self.min = min;
+        self.max = max;
+        self.level = clamp(level, min, max);
+        true
+    }
 }
 
 #[derive(Derivative)]
 #[derivative(Debug)]
 struct Toggle {
-    name: &'static str,
+    name: Cow<'static, str>,
+    id: Id,
     #[derivative(Debug="ignore")]
     text: Option<Texture>,
     rect: Option<Rect>,
@@ -136,20 +251,22 @@ struct Toggle {
     state: bool,
 }
 
+impl Drop for Toggle {
+    fn drop(&mut self) {
+        destroy(self.text.take());
+    }
+}
+
 impl Buttonish for Toggle {
-    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, selected: bool, offset_y: i32) {
-        if selected {
-            self.text.as_mut().unwrap().set_color_mod(255, 0, 0);
-        } else {
-            self.text.as_mut().unwrap().set_color_mod(255, 255, 255);
-        }
+    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, theme: &Theme, _: &mut TextRenderer, selected: bool, offset_y: i32) {
+        theme.tint(self.text.as_mut().unwrap(), selected);
 
         let mut rect = self.rect.unwrap().clone();
         rect.set_y(rect.y() + offset_y);
 
         canvas.copy(self.text.as_ref().unwrap(), None, rect).unwrap();
 
-        canvas.set_draw_color(Color::RGB(255, 255, 255));
+        canvas.set_draw_color(theme.foreground);
         self.state_rect.set_y(rect.y());
         if self.state == true {
             canvas.fill_rect(self.state_rect);
@@ -158,21 +275,37 @@ impl Buttonish for Toggle {
         }
     }
     fn captures_input(&self) -> bool { false }
-    fn action(&mut self, _: &InternalTkEvent) -> Option<TkEvent> {
+    fn action(&mut self, tab: &Id, _: &InternalTkEvent) -> Option<TkEvent> {
         self.state = !self.state;
-        Some(TkEvent::ToggleChange(self.name().to_string(), self.state))
+        Some(TkEvent::ToggleChange(tab.clone(), self.id.clone(), self.state))
     }
-    fn name(&self) -> &str { self.name }
+    fn name(&self) -> &str { &self.name }
+    fn id(&self) -> &Id { &self.id }
+    fn set_id(&mut self, id: Id) { self.id = id }
     fn rect(&self) -> Rect { self.rect.unwrap() }
+    fn set_label(&mut self, name: Cow<'static, str>, text: Texture, padding: u32) {
+        let attr = text.query();
+        let y = self.rect.unwrap().y();
+        self.rect = Some(Rect::new(0, y, attr.width, attr.height));
+        self.state_rect = Rect::new((attr.width + padding) as i32, self.state_rect.y(), attr.height, attr.height);
+        self.name = name;
+        destroy(self.text.replace(text));
+    }
+    fn set_y(&mut self, y: i32) { self.rect.as_mut().unwrap().set_y(y) }
+    fn set_state(&mut self, state: bool) -> bool {
+        self.state = state;
+        true
+    }
 }
 
 impl Toggle {
-    fn new(name: &'static str, line: usize, text: Texture) -> Toggle {
+    fn new(name: Cow<'static, str>, line: usize, padding: u32, text: Texture) -> Toggle {
         let attr = text.query();
         let rect = Rect::new(0, (line as u32 * attr.height) as i32, attr.width, attr.height);
-        let state_rect = Rect::new((attr.width + 5) as i32, (line as u32 * attr.height) as i32, attr.height, attr.height);
+        let state_rect = Rect::new((attr.width + padding) as i32, (line as u32 * attr.height) as i32, attr.height, attr.height);
         Toggle {
             name,
+            id: Id::unique(),
             text: Some(text),
             rect: Some(rect),
             state_rect,
@@ -184,348 +317,2087 @@ impl Toggle {
 #[derive(Derivative)]
 #[derivative(Debug)]
 struct Button {
-    name: &'static str,
+    name: Cow<'static, str>,
+    id: Id,
     #[derivative(Debug="ignore")]
     text: Option<Texture>,
     rect: Option<Rect>,
+    #[derivative(Debug="ignore")]
+    icon: Option<Texture>,
+    // the icon and the gap after it, 0 without one
+    icon_width: u32,
+}
+
+impl Drop for Button {
+    fn drop(&mut self) {
+        destroy(self.text.take());
+        destroy(self.icon.take());
+    }
 }
 
 impl Buttonish for Button {
-    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, selected: bool, offset_y: i32) {
-        if selected {
-            self.text.as_mut().unwrap().set_color_mod(255, 0, 0);
-        } else {
-            self.text.as_mut().unwrap().set_color_mod(255, 255, 255);
-        }
+    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, theme: &Theme, _: &mut TextRenderer, selected: bool, offset_y: i32) {
+        theme.tint(self.text.as_mut().unwrap(), selected);
 
         let mut rect = self.rect.unwrap().clone();
         rect.set_y(rect.y() + offset_y);
+        if let Some(icon) = &self.icon {
+            canvas.copy(icon, None, fit(icon, Rect::new(rect.x(), rect.y(), rect.height(), rect.height()))).unwrap();
+            rect = Rect::new(rect.x() + self.icon_width as i32, rect.y(), rect.width() - self.icon_width, rect.height());
+        }
         canvas.copy(self.text.as_ref().unwrap(), None, rect).unwrap();
     }
     fn captures_input(&self) -> bool { false }
-    fn action(&mut self, _: &InternalTkEvent) -> Option<TkEvent> {
-        Some(TkEvent::ButtonPress(self.name().to_string()))
+    fn action(&mut self, tab: &Id, _: &InternalTkEvent) -> Option<TkEvent> {
+        Some(TkEvent::ButtonPress(tab.clone(), self.id.clone()))
     }
-    fn name(&self) -> &str { self.name }
+    fn name(&self) -> &str { &self.name }
+    fn id(&self) -> &Id { &self.id }
+    fn set_id(&mut self, id: Id) { self.id = id }
     fn rect(&self) -> Rect { self.rect.unwrap() }
+    fn set_label(&mut self, name: Cow<'static, str>, text: Texture, padding: u32) {
+        let attr = text.query();
+        let y = self.rect.unwrap().y();
+        if self.icon.is_some() {
+            self.icon_width = attr.height + padding;
+        }
+        self.rect = Some(Rect::new(0, y, self.icon_width + attr.width, attr.height));
+        self.name = name;
+        destroy(self.text.replace(text));
+    }
+    fn set_y(&mut self, y: i32) { self.rect.as_mut().unwrap().set_y(y) }
+    fn set_icon(&mut self, icon: Texture, padding: u32) -> bool {
+        let rect = self.rect.unwrap();
+        let text_width = rect.width() - self.icon_width;
+        // square, as tall as the label
+        self.icon_width = rect.height() + padding;
+        self.rect = Some(Rect::new(rect.x(), rect.y(), self.icon_width + text_width, rect.height()));
+        destroy(self.icon.replace(icon));
+        true
+    }
 }
 
 impl Button {
-    fn new(name: &'static str, line: usize, text: Texture) -> Button {
+    fn new(name: Cow<'static, str>, line: usize, text: Texture) -> Button {
         let attr = text.query();
         let rect = Rect::new(0, (line as u32 * attr.height) as i32, attr.width, attr.height);
         Button {
             name,
+            id: Id::unique(),
             text: Some(text),
             rect: Some(rect),
+            icon: None,
+            icon_width: 0,
         }
     }
 }
 
+// Text that can't be selected, for headers and descriptions.
+// Paragraphs are wrapped to the window's width when rendered.
 #[derive(Derivative)]
 #[derivative(Debug)]
-struct Tab {
-    name: &'static str,
-    buttons: Vec<Box<dyn Buttonish>>,
-    btn_pos: usize,
-    max_btn_pos: usize,
+struct Label {
+    name: Cow<'static, str>,
+    id: Id,
     #[derivative(Debug="ignore")]
     text: Option<Texture>,
     rect: Option<Rect>,
+    wrapped: bool,
 }
 
-impl Tab {
-    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, selected: bool, y_offset: i32) {
-        if selected {
-            let bottom = self.rect.unwrap().height() as i32;
+impl Label {
+    fn new(name: Cow<'static, str>, text: Texture, wrapped: bool) -> Label {
+        let attr = text.query();
+        Label {
+            name,
+            id: Id::unique(),
+            text: Some(text),
+            rect: Some(Rect::new(0, 0, attr.width, attr.height)),
+            wrapped,
+        }
+    }
+}
 
-            canvas.set_draw_color(Color::RGB(255, 255, 255));
-            canvas.draw_line((0, bottom), (640, bottom)).unwrap();
-            let old = canvas.viewport();
-            let new = Rect::new(0, bottom, 640, (480 - bottom) as u32);
-            canvas.set_viewport(new);
+impl Drop for Label {
+    fn drop(&mut self) {
+        destroy(self.text.take());
+    }
+}
 
-            for (i, btn) in self.buttons.iter_mut().enumerate() {
-                btn.draw(canvas, self.btn_pos == i, y_offset);
-            }
-            canvas.set_viewport(old);
+impl Buttonish for Label {
+    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, theme: &Theme, _: &mut TextRenderer, _: bool, offset_y: i32) {
+        theme.tint(self.text.as_mut().unwrap(), false);
 
-            self.text.as_mut().unwrap().set_color_mod(255, 0, 0);
-        } else {
-            self.text.as_mut().unwrap().set_color_mod(255, 255, 255);
-        }
-        canvas.copy(self.text.as_ref().unwrap(), None, self.rect);
-    }
-    fn cur_btn(&self) -> Option<&Box<dyn Buttonish>> {
-        self.buttons.get(self.btn_pos)
+        let mut rect = self.rect.unwrap();
+        rect.set_y(rect.y() + offset_y);
+        canvas.copy(self.text.as_ref().unwrap(), None, rect).unwrap();
     }
-    fn cur_mut_btn(&mut self) -> Option<&mut Box<dyn Buttonish>> {
-        self.buttons.get_mut(self.btn_pos)
+    fn captures_input(&self) -> bool { false }
+    fn focusable(&self) -> bool { false }
+    fn wraps(&self) -> bool { self.wrapped }
+    fn action(&mut self, _: &Id, _: &InternalTkEvent) -> Option<TkEvent> { None }
+    fn name(&self) -> &str { &self.name }
+    fn id(&self) -> &Id { &self.id }
+    fn set_id(&mut self, id: Id) { self.id = id }
+    fn rect(&self) -> Rect { self.rect.unwrap() }
+    fn set_label(&mut self, name: Cow<'static, str>, text: Texture, _: u32) {
+        let attr = text.query();
+        let y = self.rect.unwrap().y();
+        self.rect = Some(Rect::new(0, y, attr.width, attr.height));
+        self.name = name;
+        destroy(self.text.replace(text));
     }
+    fn set_y(&mut self, y: i32) { self.rect.as_mut().unwrap().set_y(y) }
 }
 
-#[derive(Debug, PartialEq)]
-enum InternalTkEvent {
-    ChangeTabPos(i32),
-    ChangeBtnPos(i32),
-    Press,
-    TouchPress(i32, i32),
-    SetOffsetY(i32),
-    AppendOffsetY(i32),
-    Quit,
-    Dummy,
+// a horizontal line across the whole tab
+#[derive(Debug)]
+struct Separator {
+    id: Id,
+    rect: Option<Rect>,
 }
 
-#[derive(Debug, PartialEq)]
-pub enum TkEvent {
-    ButtonSelect(String),
-    ButtonPress(String),
-    SliderChange(String, i32, i32, i32),
-    ToggleChange(String, bool),
-    TabChange(String),
-    None,
+impl Separator {
+    fn new(height: u32) -> Separator {
+        Separator {
+            id: Id::unique(),
+            rect: Some(Rect::new(0, 0, 1, height)),
+        }
+    }
+}
+
+impl Buttonish for Separator {
+    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, theme: &Theme, _: &mut TextRenderer, _: bool, offset_y: i32) {
+        let y = self.rect.unwrap().center().y() + offset_y;
+        let width = canvas.viewport().width() as i32;
+        canvas.set_draw_color(theme.disabled);
+        canvas.draw_line((0, y), (width, y)).unwrap();
+    }
+    fn captures_input(&self) -> bool { false }
+    fn focusable(&self) -> bool { false }
+    fn has_label(&self) -> bool { false }
+    fn action(&mut self, _: &Id, _: &InternalTkEvent) -> Option<TkEvent> { None }
+    fn name(&self) -> &str { "" }
+    fn id(&self) -> &Id { &self.id }
+    fn set_id(&mut self, id: Id) { self.id = id }
+    fn rect(&self) -> Rect { self.rect.unwrap() }
+    fn set_label(&mut self, _: Cow<'static, str>, _: Texture, _: u32) {}
+    fn set_y(&mut self, y: i32) { self.rect.as_mut().unwrap().set_y(y) }
 }
 
+// An image on its own line, pressing it works like a button
 #[derive(Derivative)]
 #[derivative(Debug)]
-pub struct Toolkit {
-    tabs: Vec<Tab>,
-    tab_pos: usize,
-    max_tab_pos: usize,
-    #[derivative(Debug="ignore")]
-    canvas: sdl2::render::Canvas<sdl2::video::Window>,
-    run: bool,
-
-    #[derivative(Debug="ignore")]
-    event_pump: sdl2::EventPump,
+struct Picture {
+    name: Cow<'static, str>,
+    id: Id,
+    // None if the image couldn't be made into a texture,
+    // it still takes up its space so the widgets after it stay put
     #[derivative(Debug="ignore")]
-    event_sender: EventSender,
-
-    old_xy: Option<(i32, i32)>,
-    y_offset: i32,
-    y_velocity: i32,
-    tk_event_queue: VecDeque<TkEvent>,
-    redirect_input: bool,
-    line_height: i32,
+    image: Option<Texture>,
+    size: (u32, u32),
+    rect: Option<Rect>,
 }
 
-impl Toolkit {
-    pub fn tick(&mut self) -> bool {
-        let mut redraw = false;
-        // blocking!
-        //let ev = self.event_pump.wait_event();
-        let mut events: Vec<InternalTkEvent> = Vec::new();
-        for ev in self.event_pump.poll_iter() {
-            if ev.is_user_event() {
-                events.push(ev.as_user_event_type::<InternalTkEvent>().unwrap());
-            } else {
-                let out = match ev {
-                    Event::Quit {..} => InternalTkEvent::Quit,
-                    Event::KeyDown {keycode, ..} => {
-                        match keycode {
-                            Some(Keycode::Escape) =>    InternalTkEvent::Quit,
-                            Some(Keycode::Up) =>        InternalTkEvent::ChangeBtnPos(-1),
-                            Some(Keycode::Down) =>      InternalTkEvent::ChangeBtnPos(1),
-                            Some(Keycode::Left) =>      InternalTkEvent::ChangeTabPos(-1),
-                            Some(Keycode::Right) =>     InternalTkEvent::ChangeTabPos(1),
-                            Some(Keycode::Return) =>    InternalTkEvent::Press,
-                            _ => InternalTkEvent::Dummy,
-                        }
-                    }
-                    Event::MouseMotion {x, y, yrel, mousestate, ..} => {
-                        if mousestate.left() {
-                            if yrel == 0 {
-                                InternalTkEvent::TouchPress(x, y)
-                            } else {
-                                InternalTkEvent::AppendOffsetY(yrel)
-                            }
-                        } else {
-                            InternalTkEvent::Dummy
-                        }
-                    }
-                    Event::MouseButtonDown{x, y, mouse_btn, ..} => {
-                        if mouse_btn == MouseButton::Left {
-                            InternalTkEvent::TouchPress(x, y)
-                        } else {
-                            InternalTkEvent::Dummy
-                        }
-                    }
-                    _ => InternalTkEvent::Dummy,
-                };
-
-                if out != InternalTkEvent::Dummy {
-                    events.push(out);
-                }
-            }
-        }
-
-        for tk_ev in events {
-            if self.redirect_input && tk_ev != InternalTkEvent::Quit {
-                if let Some(btn) = self.cur_mut_btn() {
-                    if let Some(new_ev) = btn.action(&tk_ev) {
-                        self.tk_event_queue.push_back(new_ev);
-                    } else {
-                        self.redirect_input = false;
-                    }
-                    redraw = true;
-                }
-            } else {
-                match tk_ev {
-                    InternalTkEvent::ChangeTabPos(p) => {
-                        let new_pos = clamp(self.tab_pos as i32 + p, 0, self.max_tab_pos as i32) as usize;
-                        if new_pos != self.tab_pos {
-                            self.y_offset = 0;
-                            self.tab_pos = new_pos;
-                            self.tk_event_queue.push_back(TkEvent::TabChange(self.cur_tab().unwrap().name.to_string()));
-                            redraw = true;
-                        };
-                    },
-                    InternalTkEvent::ChangeBtnPos(p) => {
-                        if let Some(mut tab) = self.cur_mut_tab() {
-                            let new_pos = clamp(tab.btn_pos as i32 + p, 0, tab.max_btn_pos as i32) as usize;
-                            if new_pos != tab.btn_pos {
-                                tab.btn_pos = new_pos;
-                                let bottom = if let Some(btn) = self.cur_tab().unwrap().buttons.last() {
-                                    btn.rect().bottom()
-                                } else { unreachable!(); };
-
-                                self.y_offset = clamp(self.y_offset - self.line_height * p, -1*(bottom - (480 - self.line_height)), 0);
-                                self.tk_event_queue.push_back(TkEvent::ButtonSelect(self.cur_btn().unwrap().name().to_string()));
-                                redraw = true;
-                            }
-                        };
-                    },
-                    InternalTkEvent::Press => {
-                        if let Some(btn) = self.cur_mut_btn() {
-                            if btn.captures_input() {
-                                btn.action(&InternalTkEvent::Dummy);
-                                self.redirect_input = true;
-                            } else {
-                                if let Some(new_ev) = btn.action(&tk_ev) {
-                                    self.tk_event_queue.push_back(new_ev);
-                                }
-                            }
-                            redraw = true;
-                        };
-                    },
-                    InternalTkEvent::Quit => self.run = false,
-                    InternalTkEvent::SetOffsetY(y) => {
-                        self.y_offset = y;
-                        redraw = true;
-                    },
-                    InternalTkEvent::AppendOffsetY(y) => {
-                        self.y_offset += y;
-                        self.y_velocity = y*15;
-                        redraw = true;
-                    },
-                    InternalTkEvent::TouchPress(x, y) => {
-                        let adj_y = y - (self.line_height + self.y_offset);
-                        if y < self.line_height {
-                            let mut new_tab: Option<usize> = None;
-                            for (i, candidate) in self.tabs.iter().enumerate() {
-                                println!("considering {}", candidate.name);
-                                if candidate.rect.unwrap().contains_point(Point::new(x, y)) {
-                                    new_tab = Some(i);
-                                    break;
-                                }
-                            }
-                            if let Some(id) = new_tab {
-                                self.tab_pos = id;
-                                self.tk_event_queue.push_back(TkEvent::TabChange(self.cur_tab().unwrap().name.to_string()));
-                                redraw = true;
-                            }
-                        } else {
-                            let h = self.line_height;
-                            if let Some(mut tab) = self.cur_mut_tab() {
-                                let new_pos = clamp(adj_y / h, 0, tab.max_btn_pos as i32) as usize;
-                                if new_pos == tab.btn_pos {
-                                    if let Some(btn) = self.cur_mut_btn() {
-                                        if btn.captures_input() {
-                                            self.redirect_input = true;
-                                        } else {
-                                            if let Some(new_ev) = btn.action(&tk_ev) {
-                                                self.tk_event_queue.push_back(new_ev);
-                                                redraw = true;
-                                            }
-                                        }
-                                    };
-                                } else {
-                                    tab.btn_pos = new_pos;
-                                    self.tk_event_queue.push_back(TkEvent::ButtonSelect(self.cur_btn().unwrap().name().to_string()));
-                                    redraw = true;
-                                }
-                            }
-                        }
-                    }
-                    InternalTkEvent::Dummy => (),
-                }
-            }
-        }
-
-        if self.y_velocity != 0 {
-            self.y_offset += self.y_velocity/15;
-            if self.y_velocity > 0 {
-                self.y_velocity -= 1;
-            } else {
-                self.y_velocity += 1;
-            }
-            redraw = true;
-        }
-
-        if self.y_offset > 0 {
-            self.y_offset /= 2;
-            redraw = true;
+impl Picture {
+    fn new(image: &Image, creator: &TextureCreator<WindowContext>, scale: f32) -> Picture {
+        let texture = image.texture(creator)
+            .map_err(|e| eprintln!("gamepad_gui: can't show image, {}", e))
+            .ok();
+        let size = (image.width(), image.height());
+        Picture {
+            name: Cow::Borrowed(""),
+            id: Id::unique(),
+            image: texture,
+            size,
+            rect: Some(Rect::new(0, 0, (size.0 as f32 * scale) as u32, (size.1 as f32 * scale) as u32)),
         }
+    }
+}
 
+impl Drop for Picture {
+    fn drop(&mut self) {
+        destroy(self.image.take());
+    }
+}
 
-        if let Some(btn) = self.cur_tab().unwrap().buttons.last() {
-            let bottom = btn.rect().bottom();
-            let diff = (480 - self.line_height) - (bottom + self.y_offset);
-            if diff > 0 && self.y_offset < -480 {
-                self.y_offset += diff/2;
-                redraw = true;
-            } else if (480 - self.line_height) - bottom > 0 && self.y_offset != 0 {
-                self.y_offset = 0;
-                redraw = true;
-            }
+impl Buttonish for Picture {
+    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, theme: &Theme, _: &mut TextRenderer, selected: bool, offset_y: i32) {
+        let mut rect = self.rect.unwrap();
+        rect.set_y(rect.y() + offset_y);
+        if let Some(image) = &self.image {
+            canvas.copy(image, None, rect).unwrap();
         }
-
-        if redraw {
-            self.canvas.set_draw_color(Color::RGB(0, 0, 0));
-            self.canvas.clear();
-            for (i, tab) in self.tabs.iter_mut().enumerate() {
-                tab.draw(&mut self.canvas, self.tab_pos == i, self.y_offset);
-            }
-            self.canvas.present();
+        if selected {
+            canvas.set_draw_color(theme.accent);
+            canvas.draw_rect(rect).unwrap();
         }
-
-        self.run
     }
-
-    pub fn builder(name: &'static str) -> ToolkitBuilder {
-        ToolkitBuilder::new(name)
+    fn captures_input(&self) -> bool { false }
+    fn action(&mut self, tab: &Id, _: &InternalTkEvent) -> Option<TkEvent> {
+        Some(TkEvent::ButtonPress(tab.clone(), self.id.clone()))
     }
-
-    pub fn poll_events(&mut self) -> Option<TkEvent> {
-        self.tk_event_queue.pop_front()
+    fn name(&self) -> &str { &self.name }
+    fn id(&self) -> &Id { &self.id }
+    fn set_id(&mut self, id: Id) { self.id = id }
+    fn rect(&self) -> Rect { self.rect.unwrap() }
+    // the label isn't shown, only kept for name()
+    fn set_label(&mut self, name: Cow<'static, str>, text: Texture, _: u32) {
+        self.name = name;
+        destroy(Some(text));
     }
-
-    fn cur_mut_tab(&mut self) -> Option<&mut Tab> {
-        self.tabs.get_mut(self.tab_pos)
+    fn set_y(&mut self, y: i32) { self.rect.as_mut().unwrap().set_y(y) }
+    fn rescale(&mut self, ctx: &mut DrawContext) {
+        let (size, scale) = (self.size, ctx.scale());
+        let y = self.rect.unwrap().y();
+        self.rect = Some(Rect::new(0, y, (size.0 as f32 * scale) as u32, (size.1 as f32 * scale) as u32));
     }
-    fn cur_tab(&self) -> Option<&Tab> {
-        self.tabs.get(self.tab_pos)
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct TextInput {
+    name: Cow<'static, str>,
+    id: Id,
+    #[derivative(Debug="ignore")]
+    text: Option<Texture>,
+    rect: Option<Rect>,
+    value_rect: Rect,
+    value: String,
+    // rendered on the next draw after the value changes
+    #[derivative(Debug="ignore")]
+    value_text: Option<Texture>,
+    masked: bool,
+    #[derivative(Debug="ignore")]
+    keyboard: Keyboard,
+}
+
+impl TextInput {
+    fn new(name: Cow<'static, str>, text: Texture, line: usize, padding: u32, value: String, masked: bool) -> TextInput {
+        let attr = text.query();
+        let rect = Rect::new(0, (line as u32 * attr.height) as i32, attr.width, attr.height);
+        TextInput {
+            name,
+            id: Id::unique(),
+            text: Some(text),
+            rect: Some(rect),
+            value_rect: Rect::new((attr.width + padding) as i32, rect.y(), attr.height*5, attr.height),
+            value,
+            value_text: None,
+            masked,
+            keyboard: Keyboard::new(),
+        }
+    }
+    fn shown(&self) -> String {
+        if self.masked {
+            "*".repeat(self.value.chars().count())
+        } else {
+            self.value.clone()
+        }
+    }
+}
+
+impl Drop for TextInput {
+    fn drop(&mut self) {
+        destroy(self.text.take());
+        destroy(self.value_text.take());
+    }
+}
+
+impl Buttonish for TextInput {
+    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, theme: &Theme, text: &mut TextRenderer, selected: bool, offset_y: i32) {
+        theme.tint(self.text.as_mut().unwrap(), selected);
+
+        let mut rect = self.rect.unwrap();
+        rect.set_y(rect.y() + offset_y);
+        canvas.copy(self.text.as_ref().unwrap(), None, rect).unwrap();
+
+        self.value_rect.set_y(rect.y());
+        if self.keyboard.is_open() {
+            canvas.set_draw_color(theme.accent);
+        } else {
+            canvas.set_draw_color(theme.foreground);
+        }
+        canvas.draw_rect(self.value_rect).unwrap();
+
+        if self.value_text.is_none() {
+            let shown = self.shown();
+            self.value_text = Some(text.render(&shown, ui_scale(canvas)));
+        }
+        let value_text = self.value_text.as_mut().unwrap();
+        theme.tint(value_text, false);
+        // whatever doesn't fit in the box is cut off
+        let attr = value_text.query();
+        let width = std::cmp::min(attr.width, self.value_rect.width().saturating_sub(theme.padding * 2));
+        let src = Rect::new(0, 0, width, attr.height);
+        let dst = Rect::new(self.value_rect.x() + theme.padding as i32, rect.y(), width, attr.height);
+        canvas.copy(value_text, src, dst).unwrap();
+    }
+    fn draw_overlay(&mut self, canvas: &mut Canvas<sdl2::video::Window>, theme: &Theme, text: &mut TextRenderer) {
+        if self.keyboard.is_open() {
+            self.keyboard.draw(canvas, theme, text, self.masked);
+        }
+    }
+    fn captures_input(&self) -> bool { true }
+    fn holds_input(&self) -> bool { self.keyboard.is_open() }
+    fn action(&mut self, tab: &Id, ev: &InternalTkEvent) -> Option<TkEvent> {
+        if !self.keyboard.is_open() {
+            self.keyboard.open(&self.value);
+            return Some(TkEvent::None);
+        }
+        match self.keyboard.handle(ev) {
+            KeyboardState::Open => Some(TkEvent::None),
+            KeyboardState::Confirmed => {
+                self.value = self.keyboard.buffer().to_string();
+                destroy(self.value_text.take());
+                Some(TkEvent::TextChange(tab.clone(), self.id.clone(), self.value.clone()))
+            },
+            KeyboardState::Cancelled => None,
+        }
+    }
+    fn name(&self) -> &str { &self.name }
+    fn id(&self) -> &Id { &self.id }
+    fn set_id(&mut self, id: Id) { self.id = id }
+    fn rect(&self) -> Rect { self.rect.unwrap() }
+    fn set_label(&mut self, name: Cow<'static, str>, text: Texture, padding: u32) {
+        let attr = text.query();
+        let y = self.rect.unwrap().y();
+        self.rect = Some(Rect::new(0, y, attr.width, attr.height));
+        self.value_rect = Rect::new((attr.width + padding) as i32, self.value_rect.y(), attr.height*5, attr.height);
+        self.name = name;
+        destroy(self.text.replace(text));
+        // the value goes with the label's size
+        destroy(self.value_text.take());
+    }
+    fn set_y(&mut self, y: i32) { self.rect.as_mut().unwrap().set_y(y) }
+    fn set_text(&mut self, value: &str) -> bool {
+        self.value = value.to_string();
+        destroy(self.value_text.take());
+        true
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct Choice {
+    name: Cow<'static, str>,
+    id: Id,
+    #[derivative(Debug="ignore")]
+    text: Option<Texture>,
+    rect: Option<Rect>,
+    #[derivative(Debug="ignore")]
+    options: Vec<(Cow<'static, str>, Texture)>,
+    selected: usize,
+    options_x: i32,
+    grabbed: bool,
+}
+
+impl Choice {
+    fn new(name: Cow<'static, str>, text: Texture, line: usize, padding: u32, options: Vec<(Cow<'static, str>, Texture)>, selected: usize) -> Choice {
+        let attr = text.query();
+        let rect = Rect::new(0, (line as u32 * attr.height) as i32, attr.width, attr.height);
+        Choice {
+            name,
+            id: Id::unique(),
+            text: Some(text),
+            rect: Some(rect),
+            selected: std::cmp::min(selected, options.len().saturating_sub(1)),
+            options,
+            options_x: (attr.width + padding) as i32,
+            grabbed: false,
+        }
+    }
+}
+
+impl Drop for Choice {
+    fn drop(&mut self) {
+        destroy(self.text.take());
+        self.options.drain(..).for_each(|(_, text)| destroy(Some(text)));
+    }
+}
+
+impl Buttonish for Choice {
+    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, theme: &Theme, _: &mut TextRenderer, selected: bool, offset_y: i32) {
+        theme.tint(self.text.as_mut().unwrap(), selected);
+
+        let mut rect = self.rect.unwrap();
+        rect.set_y(rect.y() + offset_y);
+        canvas.copy(self.text.as_ref().unwrap(), None, rect).unwrap();
+
+        // sized for the widest option so it doesn't jump around while cycling
+        let widest = self.options.iter().map(|(_, t)| t.query().width).max().unwrap_or(0);
+        let outline = Rect::new(self.options_x, rect.y(), widest + theme.padding * 2, rect.height());
+        if self.grabbed {
+            canvas.set_draw_color(theme.accent);
+        } else {
+            canvas.set_draw_color(theme.foreground);
+        }
+        canvas.draw_rect(outline).unwrap();
+
+        if let Some((_, option)) = self.options.get_mut(self.selected) {
+            theme.tint(option, self.grabbed);
+            let attr = option.query();
+            let dst = Rect::new(self.options_x + theme.padding as i32, rect.y(), attr.width, attr.height);
+            canvas.copy(option, None, dst).unwrap();
+        }
+    }
+    fn captures_input(&self) -> bool { true }
+    fn action(&mut self, tab: &Id, ev: &InternalTkEvent) -> Option<TkEvent> {
+        self.grabbed = true;
+        match ev {
+            InternalTkEvent::ChangeTabPos(d) if !self.options.is_empty() => {
+                let len = self.options.len() as i32;
+                self.selected = (self.selected as i32 + d).rem_euclid(len) as usize;
+                Some(TkEvent::ChoiceChange(tab.clone(), self.id.clone(), self.selected))
+            },
+            InternalTkEvent::Press | InternalTkEvent::Back => {
+                self.grabbed = false;
+                None
+            },
+            _ => Some(TkEvent::None),
+        }
+    }
+    fn name(&self) -> &str { &self.name }
+    fn id(&self) -> &Id { &self.id }
+    fn set_id(&mut self, id: Id) { self.id = id }
+    fn rect(&self) -> Rect { self.rect.unwrap() }
+    fn set_label(&mut self, name: Cow<'static, str>, text: Texture, padding: u32) {
+        let attr = text.query();
+        let y = self.rect.unwrap().y();
+        self.rect = Some(Rect::new(0, y, attr.width, attr.height));
+        self.options_x = (attr.width + padding) as i32;
+        self.name = name;
+        destroy(self.text.replace(text));
+    }
+    fn set_y(&mut self, y: i32) { self.rect.as_mut().unwrap().set_y(y) }
+    fn set_choice(&mut self, selected: usize) -> bool {
+        if selected >= self.options.len() {
+            return false;
+        }
+        self.selected = selected;
+        true
+    }
+    fn rescale(&mut self, ctx: &mut DrawContext) {
+        relabel(self, ctx);
+        for (name, text) in self.options.iter_mut() {
+            destroy(Some(std::mem::replace(text, ctx.render_text(name))));
+        }
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct Submenu {
+    name: Cow<'static, str>,
+    id: Id,
+    #[derivative(Debug="ignore")]
+    text: Option<Texture>,
+    rect: Option<Rect>,
+    // None while it's open
+    page: Option<Page>,
+    padding: u32,
+}
+
+impl Submenu {
+    fn new(name: Cow<'static, str>, line: usize, padding: u32, text: Texture, page: Page) -> Submenu {
+        let attr = text.query();
+        let rect = Rect::new(0, (line as u32 * attr.height) as i32, attr.width, attr.height);
+        Submenu {
+            name,
+            id: Id::unique(),
+            text: Some(text),
+            rect: Some(rect),
+            page: Some(page),
+            padding,
+        }
+    }
+}
+
+impl Drop for Submenu {
+    fn drop(&mut self) {
+        destroy(self.text.take());
+    }
+}
+
+impl Buttonish for Submenu {
+    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, theme: &Theme, _: &mut TextRenderer, selected: bool, offset_y: i32) {
+        theme.tint(self.text.as_mut().unwrap(), selected);
+
+        let mut rect = self.rect.unwrap();
+        rect.set_y(rect.y() + offset_y);
+        canvas.copy(self.text.as_ref().unwrap(), None, rect).unwrap();
+
+        // a chevron after the label
+        let x = rect.right() + self.padding as i32;
+        let h = rect.height() as i32;
+        if selected {
+            canvas.set_draw_color(theme.accent);
+        } else {
+            canvas.set_draw_color(theme.foreground);
+        }
+        canvas.draw_line((x, rect.y() + h/4), (x + h/4, rect.y() + h/2)).unwrap();
+        canvas.draw_line((x + h/4, rect.y() + h/2), (x, rect.y() + h*3/4)).unwrap();
+    }
+    fn captures_input(&self) -> bool { false }
+    fn action(&mut self, tab: &Id, _: &InternalTkEvent) -> Option<TkEvent> {
+        Some(TkEvent::SubmenuOpen(tab.clone(), self.id.clone()))
+    }
+    fn name(&self) -> &str { &self.name }
+    fn id(&self) -> &Id { &self.id }
+    fn set_id(&mut self, id: Id) { self.id = id }
+    fn rect(&self) -> Rect { self.rect.unwrap() }
+    fn set_label(&mut self, name: Cow<'static, str>, text: Texture, padding: u32) {
+        let attr = text.query();
+        let y = self.rect.unwrap().y();
+        self.rect = Some(Rect::new(0, y, attr.width, attr.height));
+        self.padding = padding;
+        self.name = name;
+        destroy(self.text.replace(text));
+    }
+    fn set_y(&mut self, y: i32) { self.rect.as_mut().unwrap().set_y(y) }
+    fn page(&self) -> Option<&[Box<dyn Buttonish>]> { self.page.as_deref() }
+    fn page_mut(&mut self) -> Option<&mut Page> { self.page.as_mut() }
+    fn take_page(&mut self) -> Option<Page> { self.page.take() }
+    fn restore_page(&mut self, page: Page) { self.page = Some(page) }
+    fn rescale(&mut self, ctx: &mut DrawContext) {
+        relabel(self, ctx);
+        for btn in self.page.iter_mut().flatten() {
+            btn.rescale(ctx);
+        }
+    }
+}
+
+// the tallest tab title
+fn tab_bar_height(tabs: &[Tab]) -> i32 {
+    tabs.iter().map(|tab| tab.rect.unwrap().height() as i32).max().unwrap_or(0)
+}
+
+// Where a `strip` wide tab bar in a `width` wide window is scrolled to from
+// `scroll`, just enough for `tab` to be on screen clear of the `arrow` wide
+// arrows at the ends, and never past either end of the strip.
+fn tab_bar_scroll(scroll: i32, tab: Rect, strip: i32, width: i32, arrow: i32) -> i32 {
+    if strip <= width {
+        return 0;
+    }
+    let scroll = if tab.x() - arrow < scroll {
+        tab.x() - arrow
+    } else if tab.right() + arrow > scroll + width {
+        tab.right() + arrow - width
+    } else {
+        scroll
+    };
+    clamp(scroll, 0, strip - width)
+}
+
+// whether there are tabs hidden to the left and to the right
+fn tab_bar_arrows(scroll: i32, strip: i32, width: i32) -> (bool, bool) {
+    (scroll > 0, strip - scroll > width)
+}
+
+fn contains_widget(buttons: &[Box<dyn Buttonish>], id: &Id) -> bool {
+    buttons.iter().any(|btn| btn.id() == id || btn.page().is_some_and(|page| contains_widget(page, id)))
+}
+
+// Every widget on the page as the indices leading to it, in the order widget
+// indices count them: the page's own first, then what's inside them, so
+// that grids, rows and submenus don't move the widgets after them.
+fn widget_paths(buttons: &[Box<dyn Buttonish>]) -> Vec<(Vec<usize>, Id)> {
+    widget_paths_with(buttons, &|btn| btn.page())
+}
+
+// like widget_paths(), with `page` giving what's inside each widget
+fn widget_paths_with<'a>(buttons: &'a [Box<dyn Buttonish>], page: &dyn Fn(&'a dyn Buttonish) -> Option<&'a [Box<dyn Buttonish>]>) -> Vec<(Vec<usize>, Id)> {
+    let mut paths: Vec<(Vec<usize>, Id)> = buttons.iter().enumerate().map(|(i, btn)| (vec![i], btn.id().clone())).collect();
+    for (i, btn) in buttons.iter().enumerate() {
+        for (mut path, id) in page(btn.as_ref()).map_or(Vec::new(), |inner| widget_paths_with(inner, page)) {
+            path.insert(0, i);
+            paths.push((path, id));
+        }
+    }
+    paths
+}
+
+fn widget_at<'a>(buttons: &'a mut Page, path: &[usize]) -> Option<&'a mut Box<dyn Buttonish>> {
+    let (first, rest) = path.split_first()?;
+    let btn = buttons.get_mut(*first)?;
+    if rest.is_empty() {
+        return Some(btn);
+    }
+    widget_at(btn.page_mut()?, rest)
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct Tab {
+    name: Cow<'static, str>,
+    id: Id,
+    buttons: Vec<Box<dyn Buttonish>>,
+    btn_pos: usize,
+    max_btn_pos: usize,
+    #[derivative(Debug="ignore")]
+    text: Option<Texture>,
+    rect: Option<Rect>,
+    // keyed by widget id
+    #[derivative(Debug="ignore")]
+    callbacks: HashMap<Id, Callback>,
+    // the pages below the open submenu with the index of the submenu in them
+    // and how far they were scrolled, `buttons` is whatever page is on top
+    parents: Vec<(usize, Page, i32)>,
+    #[derivative(Debug="ignore")]
+    breadcrumb: Option<Texture>,
+    #[derivative(Debug="ignore")]
+    icon: Option<Texture>,
+    icon_width: u32,
+    // everything in `buttons` plus the margins, from the last layout()
+    content_height: i32,
+}
+
+impl Drop for Tab {
+    fn drop(&mut self) {
+        destroy(self.text.take());
+        destroy(self.breadcrumb.take());
+        destroy(self.icon.take());
+    }
+}
+
+impl Tab {
+    fn new(name: Cow<'static, str>, buttons: Vec<Box<dyn Buttonish>>, text: Texture, x: i32, theme: &Theme) -> Tab {
+        let attr = text.query();
+        let mut tab = Tab {
+            name,
+            id: Id::unique(),
+            buttons,
+            btn_pos: 0,
+            max_btn_pos: 0,
+            text: Some(text),
+            rect: Some(Rect::new(x, 0, attr.width, attr.height)),
+            callbacks: HashMap::new(),
+            parents: Vec::new(),
+            breadcrumb: None,
+            icon: None,
+            icon_width: 0,
+            content_height: 0,
+        };
+        tab.layout(theme);
+        tab
+    }
+    // lay the tabs out again after, like with set_icon
+    fn set_label(&mut self, name: Cow<'static, str>, text: Texture, padding: u32) {
+        let attr = text.query();
+        if self.icon.is_some() {
+            self.icon_width = attr.height + padding;
+        }
+        let rect = self.rect.unwrap();
+        self.rect = Some(Rect::new(rect.x(), rect.y(), self.icon_width + attr.width, attr.height));
+        self.name = name;
+        destroy(self.text.replace(text));
+    }
+    // Stacks the buttons top to bottom at their own heights, call after adding,
+    // removing or resizing any. The margin on the sides is left to draw().
+    fn layout(&mut self, theme: &Theme) {
+        let mut y = theme.margin as i32;
+        for btn in self.buttons.iter_mut() {
+            btn.set_y(y);
+            y += (btn.rect().height() + theme.row_spacing) as i32;
+        }
+        if !self.buttons.is_empty() {
+            y -= theme.row_spacing as i32;
+        }
+        self.content_height = y + theme.margin as i32;
+        self.max_btn_pos = self.buttons.len().saturating_sub(1);
+        self.btn_pos = std::cmp::min(self.btn_pos, self.max_btn_pos);
+        if !self.focusable(self.btn_pos) {
+            self.btn_pos = self.step(self.btn_pos, 1);
+        }
+    }
+    // the widget at `x`, `y` from the top left of the content, not counting
+    // the gaps between them or the margins on the sides of a `width` wide window
+    fn hit(&self, x: i32, y: i32, width: i32, theme: &Theme) -> Option<usize> {
+        let margin = theme.margin as i32;
+        if x < margin || x >= width - margin {
+            return None;
+        }
+        self.buttons.iter().position(|btn| btn.rect().y() <= y && y < btn.rect().bottom())
+    }
+    // roughly how many widgets fit in `height`, at least one
+    fn rows_per_page(&self, height: i32) -> i32 {
+        if self.buttons.is_empty() {
+            return 1;
+        }
+        let average = std::cmp::max(self.content_height / self.buttons.len() as i32, 1);
+        std::cmp::max(height / average - 1, 1)
+    }
+    fn focusable(&self, index: usize) -> bool {
+        self.buttons.get(index).is_some_and(|btn| btn.focusable())
+    }
+    // Where moving `d` from `from` ends up, going on past labels and
+    // separators in the same direction. If there's nothing focusable that
+    // way it backs up to the closest one, or stays at `from` if there's none.
+    fn step(&self, from: usize, d: i32) -> usize {
+        let target = clamp(from as i32 + d, 0, self.max_btn_pos as i32);
+        let dir = if d < 0 { -1 } else { 1 };
+        let mut i = target;
+        while i >= 0 && i <= self.max_btn_pos as i32 {
+            if self.focusable(i as usize) {
+                return i as usize;
+            }
+            i += dir;
+        }
+        i = target - dir;
+        while i != from as i32 && i >= 0 && i <= self.max_btn_pos as i32 {
+            if self.focusable(i as usize) {
+                return i as usize;
+            }
+            i -= dir;
+        }
+        from
+    }
+    // goes before the name, the tab gets wider so lay the tabs out again after
+    fn set_icon(&mut self, icon: Texture, padding: u32) {
+        let rect = self.rect.unwrap();
+        let text_width = rect.width() - self.icon_width;
+        self.icon_width = rect.height() + padding;
+        self.rect = Some(Rect::new(rect.x(), rect.y(), self.icon_width + text_width, rect.height()));
+        destroy(self.icon.replace(icon));
+    }
+    fn open_page(&mut self, index: usize, y_offset: i32, theme: &Theme) -> bool {
+        let page = if let Some(page) = self.buttons.get_mut(index).and_then(|btn| btn.take_page()) {
+            page
+        } else {
+            return false;
+        };
+        let parent = std::mem::replace(&mut self.buttons, page);
+        self.parents.push((index, parent, y_offset));
+        self.btn_pos = 0;
+        destroy(self.breadcrumb.take());
+        self.layout(theme);
+        true
+    }
+    // returns the id of the submenu that was closed, with its page back inside,
+    // and the offset the page under it had
+    fn close_page(&mut self, theme: &Theme) -> Option<(Id, i32)> {
+        let (index, parent, y_offset) = self.parents.pop()?;
+        let page = std::mem::replace(&mut self.buttons, parent);
+        self.buttons[index].restore_page(page);
+        self.btn_pos = index;
+        destroy(self.breadcrumb.take());
+        self.layout(theme);
+        Some((self.buttons[index].focus_id().clone(), y_offset))
+    }
+    // Runs `f` on the tab's first page with the open submenus' pages put back
+    // inside them, so widget indices mean the same whichever page is showing.
+    fn with_root<R>(&mut self, f: impl FnOnce(&mut Page) -> R) -> R {
+        let btn_pos = self.btn_pos;
+        let mut open = Vec::new();
+        while let Some((index, parent, y_offset)) = self.parents.pop() {
+            let page = std::mem::replace(&mut self.buttons, parent);
+            self.buttons[index].restore_page(page);
+            open.push((index, y_offset));
+        }
+        let result = f(&mut self.buttons);
+        while let Some((index, y_offset)) = open.pop() {
+            let page = self.buttons[index].take_page().unwrap();
+            let parent = std::mem::replace(&mut self.buttons, page);
+            self.parents.push((index, parent, y_offset));
+        }
+        self.btn_pos = btn_pos;
+        // in case a submenu on the way was renamed
+        destroy(self.breadcrumb.take());
+        result
+    }
+    // ids in the order widget indices count them, from the first page like with_root()
+    fn widget_ids(&self) -> Vec<Id> {
+        // each open submenu's page is the one over the page it was opened from
+        let open: Vec<(&Id, &Page)> = self.parents.iter().enumerate()
+            .map(|(depth, (index, parent, _))| {
+                let page = self.parents.get(depth + 1).map_or(&self.buttons, |(_, page, _)| page);
+                (parent[*index].focus_id(), page)
+            })
+            .collect();
+        let root = self.parents.first().map_or(&self.buttons, |(_, page, _)| page);
+        let paths = widget_paths_with(root, &|btn| btn.page().or_else(|| {
+            open.iter().find(|(id, _)| btn.id() == *id).map(|(_, page)| page.as_slice())
+        }));
+        paths.into_iter().map(|(_, id)| id).collect()
+    }
+    fn contains(&self, id: &Id) -> bool {
+        contains_widget(&self.buttons, id) || self.parents.iter().any(|(_, page, _)| contains_widget(page, id))
+    }
+    // `area` is everything under the tab bar, moved sideways while sliding in,
+    // `scroll` is the tab bar's horizontal scroll and the content's vertical offset
+    fn draw(&mut self, canvas: &mut Canvas<sdl2::video::Window>, theme: &Theme, text: &mut TextRenderer, selected: bool, scroll: Point, area: Rect) {
+        let y_offset = scroll.y();
+        let width = area.width() as i32;
+        if selected {
+            let mut bottom = area.y();
+
+            canvas.set_draw_color(theme.foreground);
+            canvas.draw_line((0, bottom), (width, bottom)).unwrap();
+
+            // path to the open submenu under the tabs
+            if !self.parents.is_empty() {
+                if self.breadcrumb.is_none() {
+                    let path: Vec<&str> = self.parents.iter().map(|(i, page, _)| page[*i].name()).collect();
+                    self.breadcrumb = Some(text.render(&path.join(" > "), ui_scale(canvas)));
+                }
+                let breadcrumb = self.breadcrumb.as_mut().unwrap();
+                theme.tint(breadcrumb, false);
+                let attr = breadcrumb.query();
+                canvas.copy(breadcrumb, None, Rect::new(area.x(), bottom, attr.width, attr.height)).unwrap();
+                bottom += attr.height as i32;
+                canvas.set_draw_color(theme.foreground);
+                canvas.draw_line((0, bottom), (width, bottom)).unwrap();
+            }
+            let old = canvas.viewport();
+            let margin = theme.margin as i32;
+            let new = Rect::new(area.x() + margin, bottom, (width - margin * 2) as u32, (area.bottom() - bottom) as u32);
+            canvas.set_viewport(new);
+
+            for (i, btn) in self.buttons.iter_mut().enumerate() {
+                let selected = self.btn_pos == i && btn.focusable();
+                btn.draw(canvas, theme, text, selected, y_offset);
+            }
+            canvas.set_viewport(old);
+        }
+        theme.tint(self.text.as_mut().unwrap(), selected);
+        let mut rect = self.rect.unwrap();
+        rect.set_x(rect.x() - scroll.x());
+        if let Some(icon) = &self.icon {
+            canvas.copy(icon, None, fit(icon, Rect::new(rect.x(), rect.y(), rect.height(), rect.height()))).unwrap();
+        }
+        let text_rect = Rect::new(rect.x() + self.icon_width as i32, rect.y(), rect.width() - self.icon_width, rect.height());
+        canvas.copy(self.text.as_ref().unwrap(), None, text_rect);
+    }
+    fn cur_mut_btn(&mut self) -> Option<&mut Box<dyn Buttonish>> {
+        self.buttons.get_mut(self.btn_pos)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum InternalTkEvent {
+    ChangeTabPos(i32),
+    ChangeBtnPos(i32),
+    // L1/R1, always switches tabs even if the selected widget uses left/right
+    SwitchTab(i32),
+    HoldTabPos(i32),
+    HoldBtnPos(i32),
+    ChangePage(i32),
+    Press,
+    Back,
+    TouchPress(i32, i32),
+    GamepadConnected(String),
+    GamepadDisconnected(String),
+    SetOffsetY(i32),
+    AppendOffsetY(i32),
+    Resize,
+    Quit,
+    Dummy,
+}
+
+// Identifies a tab or widget. Everything gets a unique one by default,
+// set your own with TabBuilder::with_id and TabBuilder::tab_id.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Id {
+    Auto(u32),
+    Named(Cow<'static, str>),
+}
+
+impl Id {
+    pub fn unique() -> Id {
+        static NEXT: AtomicU32 = AtomicU32::new(0);
+        Id::Auto(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl From<&'static str> for Id {
+    fn from(name: &'static str) -> Id {
+        Id::Named(Cow::Borrowed(name))
+    }
+}
+
+impl From<String> for Id {
+    fn from(name: String) -> Id {
+        Id::Named(Cow::Owned(name))
+    }
+}
+
+impl std::fmt::Display for Id {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Id::Auto(n) => write!(f, "#{}", n),
+            Id::Named(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+// widget events are (tab id, widget id, ...)
+#[derive(Debug, PartialEq)]
+pub enum TkEvent {
+    ButtonSelect(Id, Id),
+    ButtonPress(Id, Id),
+    SliderChange(Id, Id, i32, i32, i32),
+    ToggleChange(Id, Id, bool),
+    TextChange(Id, Id, String),
+    ChoiceChange(Id, Id, usize),
+    // dialog id, true if confirmed, alerts are always true
+    DialogResult(Id, bool),
+    // whatever a custom widget emitted
+    Custom(Id, Id, i32),
+    TabChange(Id),
+    // (tab id, submenu id)
+    SubmenuOpen(Id, Id),
+    SubmenuClose(Id, Id),
+    // Back on a tab without an open submenu
+    Back,
+    GamepadConnected(String),
+    GamepadDisconnected(String),
+    None,
+}
+
+pub type Callback = Box<dyn FnMut(&mut Context)>;
+
+// Handed to widget callbacks, derefs to the toolkit so a callback
+// can do anything the app could do between ticks.
+pub struct Context<'a> {
+    tk: &'a mut Toolkit,
+    event: &'a TkEvent,
+}
+
+impl Context<'_> {
+    // the widget event that fired the callback
+    pub fn event(&self) -> &TkEvent {
+        self.event
+    }
+}
+
+impl std::ops::Deref for Context<'_> {
+    type Target = Toolkit;
+    fn deref(&self) -> &Toolkit {
+        self.tk
+    }
+}
+
+impl std::ops::DerefMut for Context<'_> {
+    fn deref_mut(&mut self) -> &mut Toolkit {
+        self.tk
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Toolkit {
+    tabs: Vec<Tab>,
+    tab_pos: usize,
+    max_tab_pos: usize,
+    #[derivative(Debug="ignore")]
+    canvas: sdl2::render::Canvas<sdl2::video::Window>,
+    run: bool,
+
+    #[derivative(Debug="ignore")]
+    event_pump: sdl2::EventPump,
+    #[derivative(Debug="ignore")]
+    event_sender: EventSender,
+
+    old_xy: Option<(i32, i32)>,
+    y_offset: i32,
+    // pixels per second from the last flick, and what's left over from moving less than a pixel
+    y_velocity: f32,
+    y_fraction: f32,
+    // how far the tab bar is scrolled to the left when the tabs don't fit
+    tab_scroll: i32,
+    // y_offset and tab_scroll are where things are going, these are what's on screen
+    animation: AnimationConfig,
+    last_tick: Instant,
+    // scripted input runs on its own time, see Toolkit::play,
+    // which leaves the clock ahead of the real one
+    clock: Option<Instant>,
+    clock_ahead: Duration,
+    shown_y: Tween,
+    shown_tab_scroll: Tween,
+    // the content slides in from the side on tab switches
+    tab_slide: Tween,
+    // whether anything was still moving on the last tick
+    animating: bool,
+    // the selection outline moves from the last selected widget to the new one,
+    // 0.0 to 1.0 along the way
+    focus_ring: Tween,
+    focus_from: Rect,
+    // (tab, submenu depth, focus rect) as of the last tick
+    last_focus: (usize, usize, Rect),
+    tk_event_queue: VecDeque<TkEvent>,
+    redirect_input: bool,
+    // changed from outside tick(), drawn on the next one
+    dirty: bool,
+    line_height: i32,
+    width: i32,
+    height: i32,
+    theme: Theme,
+    #[derivative(Debug="ignore")]
+    text: TextRenderer,
+    btn_repeat: Repeater,
+    tab_repeat: Repeater,
+    input_map: InputMap,
+    #[derivative(Debug="ignore")]
+    controller_subsystem: GameControllerSubsystem,
+    #[derivative(Debug="ignore")]
+    controllers: Vec<GameController>,
+    // only the first one is shown
+    #[derivative(Debug="ignore")]
+    dialogs: VecDeque<Dialog>,
+    #[derivative(Debug="ignore")]
+    dialog_callbacks: HashMap<Id, Callback>,
+}
+
+// the textures all came from the renderer, they have to go before it does
+impl Drop for Toolkit {
+    fn drop(&mut self) {
+        self.dialogs.clear();
+        self.tabs.clear();
+    }
+}
+
+impl Toolkit {
+    pub fn tick(&mut self) -> bool {
+        let mut redraw = false;
+        // blocking!
+        //let ev = self.event_pump.wait_event();
+        let mut events: Vec<InternalTkEvent> = Vec::new();
+        for ev in self.event_pump.poll_iter() {
+            if ev.is_user_event() {
+                events.push(ev.as_user_event_type::<InternalTkEvent>().unwrap());
+            } else {
+                let out = match ev {
+                    Event::Quit {..} => InternalTkEvent::Quit,
+                    // we do our own key repeat
+                    Event::KeyDown {keycode: Some(key), repeat: false, ..} => {
+                        self.input_map.keyboard.get(&key)
+                            .and_then(|action| action.to_event(true))
+                            .unwrap_or(InternalTkEvent::Dummy)
+                    }
+                    Event::KeyUp {keycode: Some(key), ..} => {
+                        self.input_map.keyboard.get(&key)
+                            .and_then(|action| action.to_event(false))
+                            .unwrap_or(InternalTkEvent::Dummy)
+                    }
+                    Event::ControllerButtonDown {button, ..} => {
+                        self.input_map.controller.get(&button)
+                            .and_then(|action| action.to_event(true))
+                            .unwrap_or(InternalTkEvent::Dummy)
+                    }
+                    Event::ControllerButtonUp {button, ..} => {
+                        self.input_map.controller.get(&button)
+                            .and_then(|action| action.to_event(false))
+                            .unwrap_or(InternalTkEvent::Dummy)
+                    }
+                    Event::ControllerDeviceAdded {which, ..} => {
+                        if !self.input_map.controller.is_empty() {
+                            if let Ok(controller) = self.controller_subsystem.open(which) {
+                                self.controllers.push(controller);
+                            }
+                        }
+                        InternalTkEvent::Dummy
+                    }
+                    Event::ControllerDeviceRemoved {which, ..} => {
+                        self.controllers.retain(|c| c.instance_id() != which);
+                        InternalTkEvent::Dummy
+                    }
+                    Event::Window {win_event: WindowEvent::SizeChanged(..), ..} => InternalTkEvent::Resize,
+                    Event::MouseMotion {x, y, yrel, mousestate, ..} => {
+                        if mousestate.left() {
+                            if yrel == 0 {
+                                InternalTkEvent::TouchPress(x, y)
+                            } else {
+                                InternalTkEvent::AppendOffsetY(yrel)
+                            }
+                        } else {
+                            InternalTkEvent::Dummy
+                        }
+                    }
+                    Event::MouseButtonDown{x, y, mouse_btn, ..} => {
+                        if mouse_btn == MouseButton::Left {
+                            InternalTkEvent::TouchPress(x, y)
+                        } else {
+                            InternalTkEvent::Dummy
+                        }
+                    }
+                    _ => InternalTkEvent::Dummy,
+                };
+
+                if out != InternalTkEvent::Dummy {
+                    events.push(out);
+                }
+            }
+        }
+
+        // turn held directions into single steps
+        let now = self.now();
+        // seconds since the last tick, so motion doesn't depend on the frame rate
+        let dt = std::cmp::min(now - self.last_tick, Duration::from_millis(100)).as_secs_f32();
+        self.last_tick = now;
+        let mut dragged = false;
+        let mut drag_y = 0;
+        let mut steps: Vec<InternalTkEvent> = Vec::new();
+        for tk_ev in events {
+            match tk_ev {
+                InternalTkEvent::HoldBtnPos(d) => {
+                    if self.btn_repeat.hold(d, now) {
+                        steps.push(InternalTkEvent::ChangeBtnPos(d));
+                    }
+                },
+                InternalTkEvent::HoldTabPos(d) => {
+                    if self.tab_repeat.hold(d, now) {
+                        steps.push(InternalTkEvent::ChangeTabPos(d));
+                    }
+                },
+                InternalTkEvent::ChangePage(d) => {
+                    let page = self.cur_tab().unwrap().rows_per_page(self.height - self.content_top());
+                    steps.push(InternalTkEvent::ChangeBtnPos(d * page));
+                },
+                _ => steps.push(tk_ev),
+            }
+        }
+        if let Some(d) = self.btn_repeat.poll(now) {
+            steps.push(InternalTkEvent::ChangeBtnPos(d));
+        }
+        if let Some(d) = self.tab_repeat.poll(now) {
+            steps.push(InternalTkEvent::ChangeTabPos(d));
+        }
+
+        for tk_ev in steps {
+            match tk_ev {
+                InternalTkEvent::GamepadConnected(name) => {
+                    self.tk_event_queue.push_back(TkEvent::GamepadConnected(name));
+                    continue;
+                },
+                InternalTkEvent::GamepadDisconnected(name) => {
+                    self.tk_event_queue.push_back(TkEvent::GamepadDisconnected(name));
+                    continue;
+                },
+                _ => (),
+            }
+
+            // an open dialog or a widget holding on to input gets all of it,
+            // resizes and drags still go to the toolkit
+            let is_input = matches!(tk_ev, InternalTkEvent::ChangeTabPos(_) | InternalTkEvent::ChangeBtnPos(_) | InternalTkEvent::SwitchTab(_)
+                | InternalTkEvent::Press | InternalTkEvent::Back | InternalTkEvent::TouchPress(..));
+            if let Some(dialog) = self.dialogs.front_mut().filter(|_| is_input) {
+                if let Some(result) = dialog.handle(&tk_ev) {
+                    let id = self.dialogs.pop_front().unwrap().id.clone();
+                    self.tk_event_queue.push_back(TkEvent::DialogResult(id, result));
+                }
+                redraw = true;
+                continue;
+            }
+
+            let tab_id = self.cur_tab().unwrap().id.clone();
+            if self.redirect_input && is_input {
+                if let Some(btn) = self.cur_mut_btn() {
+                    if let Some(new_ev) = btn.action(&tab_id, &tk_ev) {
+                        self.redirect_input = btn.holds_input();
+                        self.tk_event_queue.push_back(new_ev);
+                    } else {
+                        self.redirect_input = false;
+                    }
+                    redraw = true;
+                }
+            } else {
+                match tk_ev {
+                    InternalTkEvent::ChangeTabPos(p) => {
+                        // grids and rows use left/right for themselves
+                        if let Some(btn) = self.cur_mut_btn().filter(|btn| btn.is_grid() && btn.focusable()) {
+                            let old_y = btn.focus_rect().y();
+                            if btn.navigate(p, 0) {
+                                let (id, moved) = (btn.focus_id().clone(), btn.focus_rect().y() - old_y);
+                                self.tk_event_queue.push_back(TkEvent::ButtonSelect(tab_id.clone(), id));
+                                self.scroll_by(moved);
+                                redraw = true;
+                            }
+                            continue;
+                        }
+                        if self.switch_tab(p) {
+                            redraw = true;
+                        }
+                    },
+                    InternalTkEvent::SwitchTab(p) => {
+                        if self.switch_tab(p) {
+                            redraw = true;
+                        }
+                    },
+                    InternalTkEvent::ChangeBtnPos(p) => {
+                        let line_height = self.line_height;
+                        let tab = &mut self.tabs[self.tab_pos];
+                        if !tab.buttons.is_empty() {
+                            let old_y = tab.buttons[tab.btn_pos].focus_rect().y();
+                            // inside a grid first, then on to the next widget,
+                            // or scroll a line at a time when there's nothing left to select that way
+                            let moved = if tab.buttons[tab.btn_pos].navigate(0, p) {
+                                let btn = &tab.buttons[tab.btn_pos];
+                                self.tk_event_queue.push_back(TkEvent::ButtonSelect(tab_id.clone(), btn.focus_id().clone()));
+                                redraw = true;
+                                btn.focus_rect().y() - old_y
+                            } else {
+                                let new_pos = tab.step(tab.btn_pos, p);
+                                if new_pos != tab.btn_pos {
+                                    let btn = &mut tab.buttons[new_pos];
+                                    btn.enter(p);
+                                    tab.btn_pos = new_pos;
+                                    self.tk_event_queue.push_back(TkEvent::ButtonSelect(tab_id.clone(), btn.focus_id().clone()));
+                                    redraw = true;
+                                    btn.focus_rect().y() - old_y
+                                } else {
+                                    line_height * p
+                                }
+                            };
+                            if self.scroll_by(moved) {
+                                redraw = true;
+                            }
+                        }
+                    },
+                    InternalTkEvent::Press => {
+                        if let Some(btn) = self.cur_mut_btn().filter(|btn| btn.focusable()) {
+                            if btn.captures_input() {
+                                // custom widgets can let go of input straight away
+                                let new_ev = btn.action(&tab_id, &InternalTkEvent::Dummy);
+                                self.redirect_input = new_ev.is_some() && btn.holds_input();
+                                if let Some(new_ev) = new_ev.filter(|ev| *ev != TkEvent::None) {
+                                    self.tk_event_queue.push_back(new_ev);
+                                }
+                            } else {
+                                if let Some(new_ev) = btn.action(&tab_id, &tk_ev) {
+                                    self.tk_event_queue.push_back(new_ev);
+                                }
+                            }
+                            self.open_submenu();
+                            redraw = true;
+                        };
+                    },
+                    InternalTkEvent::Back => {
+                        if let Some(submenu) = self.close_submenu() {
+                            self.tk_event_queue.push_back(TkEvent::SubmenuClose(tab_id.clone(), submenu));
+                            redraw = true;
+                        } else {
+                            self.tk_event_queue.push_back(TkEvent::Back);
+                        }
+                    },
+                    InternalTkEvent::Quit => self.run = false,
+                    InternalTkEvent::Resize => {
+                        // the event has the size in window points, everything
+                        // else goes by pixels
+                        let (w, h) = self.canvas.output_size().unwrap();
+                        self.width = w as i32;
+                        self.height = h as i32;
+                        self.rescale();
+                        redraw = true;
+                    },
+                    InternalTkEvent::SetOffsetY(y) => {
+                        self.y_offset = y;
+                        redraw = true;
+                    },
+                    InternalTkEvent::AppendOffsetY(y) => {
+                        self.y_offset += y;
+                        drag_y += y;
+                        dragged = true;
+                        redraw = true;
+                    },
+                    InternalTkEvent::TouchPress(x, y) => {
+                        let top = self.content_top();
+                        let adj_y = y - (top + self.y_offset);
+                        if y < self.line_height {
+                            // the arrows at the ends of an overflowing tab bar
+                            let (left, right) = self.tab_arrows();
+                            let arrow = self.line_height / 2;
+                            if (left && x < arrow) || (right && x >= self.width - arrow) {
+                                if self.switch_tab(if x < arrow { -1 } else { 1 }) {
+                                    redraw = true;
+                                }
+                                continue;
+                            }
+                            let mut new_tab: Option<usize> = None;
+                            for (i, candidate) in self.tabs.iter().enumerate() {
+                                if candidate.rect.unwrap().contains_point(Point::new(x + self.tab_scroll, y)) {
+                                    new_tab = Some(i);
+                                    break;
+                                }
+                            }
+                            if let Some(id) = new_tab {
+                                if self.switch_tab(id as i32 - self.tab_pos as i32) {
+                                    redraw = true;
+                                }
+                            }
+                        } else if y < top {
+                            // tapping the breadcrumb goes back
+                            if let Some(submenu) = self.close_submenu() {
+                                self.tk_event_queue.push_back(TkEvent::SubmenuClose(tab_id.clone(), submenu));
+                                redraw = true;
+                            }
+                        } else {
+                            let width = self.width;
+                            let tab = &mut self.tabs[self.tab_pos];
+                            if let Some(new_pos) = tab.hit(x, adj_y, width, &self.theme) {
+                                if !tab.focusable(new_pos) {
+                                    // labels and separators don't react to touch
+                                } else if new_pos == tab.btn_pos {
+                                    if let Some(btn) = self.cur_mut_btn() {
+                                        if btn.select_at(x, y) {
+                                            // the first tap on a grid cell only selects it
+                                            let id = btn.focus_id().clone();
+                                            self.tk_event_queue.push_back(TkEvent::ButtonSelect(tab_id.clone(), id));
+                                            redraw = true;
+                                        } else if btn.captures_input() {
+                                            let new_ev = btn.action(&tab_id, &InternalTkEvent::Dummy);
+                                            self.redirect_input = new_ev.is_some() && btn.holds_input();
+                                            if let Some(new_ev) = new_ev.filter(|ev| *ev != TkEvent::None) {
+                                                self.tk_event_queue.push_back(new_ev);
+                                            }
+                                            redraw = true;
+                                        } else {
+                                            if let Some(new_ev) = btn.action(&tab_id, &tk_ev) {
+                                                self.tk_event_queue.push_back(new_ev);
+                                                redraw = true;
+                                            }
+                                            if self.open_submenu() {
+                                                redraw = true;
+                                            }
+                                        }
+                                    };
+                                } else {
+                                    tab.btn_pos = new_pos;
+                                    let btn = &mut tab.buttons[new_pos];
+                                    btn.select_at(x, y);
+                                    let id = btn.focus_id().clone();
+                                    self.tk_event_queue.push_back(TkEvent::ButtonSelect(tab_id.clone(), id));
+                                    redraw = true;
+                                }
+                            }
+                        }
+                    }
+                    InternalTkEvent::HoldTabPos(_) | InternalTkEvent::HoldBtnPos(_) | InternalTkEvent::ChangePage(_) => unreachable!(),
+                    InternalTkEvent::GamepadConnected(_) | InternalTkEvent::GamepadDisconnected(_) => unreachable!(),
+                    InternalTkEvent::Dummy => (),
+                }
+            }
+        }
+
+        // a flick goes on at the speed of the last tick's worth of dragging
+        if dragged && self.animation.enabled && dt > 0.0 {
+            self.y_velocity = drag_y as f32 / dt;
+        }
+
+        // flicks keep going and slow down by the same amount every second
+        let min_offset = self.min_offset();
+        if self.y_velocity != 0.0 && !dragged {
+            let travel = self.y_velocity * dt + self.y_fraction;
+            self.y_offset += travel.trunc() as i32;
+            self.y_fraction = travel.fract();
+            self.y_velocity *= self.animation.friction.powf(dt);
+            if self.y_velocity.abs() < 20.0 || self.y_offset > 0 || self.y_offset < min_offset {
+                self.y_velocity = 0.0;
+                self.y_fraction = 0.0;
+            }
+            redraw = true;
+        }
+
+        // dragged past either end, spring back once the finger stops
+        if !dragged {
+            let clamped = clamp(self.y_offset, min_offset, 0);
+            if clamped != self.y_offset {
+                self.y_offset = clamped;
+                redraw = true;
+            }
+        }
+
+        self.run_callbacks();
+
+        // one more frame once it stops, so that things end up where they're going
+        let animating = self.animate(now, dragged);
+        if animating || self.animating {
+            redraw = true;
+        }
+        self.animating = animating;
+
+        if redraw || self.dirty {
+            self.dirty = false;
+            self.redraw();
+        }
+
+        self.run
+    }
+
+    // events handled by a callback don't show up in poll_events()
+    fn run_callbacks(&mut self) {
+        let mut unhandled: VecDeque<TkEvent> = VecDeque::new();
+        let pending: Vec<TkEvent> = self.tk_event_queue.drain(..).collect();
+        for ev in pending {
+            // taken out while it runs since it gets the whole toolkit
+            let callback = match &ev {
+                TkEvent::ButtonPress(tab, widget)
+                | TkEvent::ToggleChange(tab, widget, _)
+                | TkEvent::SliderChange(tab, widget, ..)
+                | TkEvent::TextChange(tab, widget, _)
+                | TkEvent::ChoiceChange(tab, widget, _)
+                | TkEvent::Custom(tab, widget, _) => {
+                    self.tab_index(tab)
+                        .and_then(|i| self.tabs[i].callbacks.remove(widget))
+                        .map(|callback| (callback, Some((tab.clone(), widget.clone()))))
+                },
+                TkEvent::DialogResult(id, _) => self.dialog_callbacks.remove(id).map(|callback| (callback, None)),
+                _ => None,
+            };
+            if let Some((mut callback, widget)) = callback {
+                callback(&mut Context { tk: self, event: &ev });
+                // dialog callbacks only run once, widget ones stay
+                // unless the callback removed its own widget
+                if let Some((tab, widget)) = widget {
+                    if let Some(t) = self.tabs.iter_mut().find(|t| t.id == tab && t.contains(&widget)) {
+                        t.callbacks.entry(widget).or_insert(callback);
+                    }
+                }
+            } else {
+                unhandled.push_back(ev);
+            }
+        }
+        // anything queued by the callbacks goes after what was already there
+        unhandled.extend(self.tk_event_queue.drain(..));
+        self.tk_event_queue = unhandled;
+    }
+
+    // tab bar plus the breadcrumb if there's a submenu open
+    fn content_top(&self) -> i32 {
+        if self.cur_tab().unwrap().parents.is_empty() {
+            self.line_height
+        } else {
+            self.line_height * 2
+        }
+    }
+
+    // how far the content can be scrolled up
+    fn min_offset(&self) -> i32 {
+        let bottom = self.cur_tab().unwrap().content_height;
+        std::cmp::min(-(bottom - (self.height - self.content_top())), 0)
+    }
+
+    // moves what's on screen towards y_offset, tab_scroll and the selection,
+    // true while there's still something moving
+    fn animate(&mut self, now: Instant, dragged: bool) -> bool {
+        let cfg = self.animation;
+        self.scroll_tabs();
+        self.shown_tab_scroll.set(self.tab_scroll as f32, &cfg, now);
+
+        let tab = &self.tabs[self.tab_pos];
+        let focus = tab.buttons.get(tab.btn_pos).map_or(Rect::new(0, 0, 1, 1), |btn| btn.focus_rect());
+        let (last_tab, last_depth, last_rect) = self.last_focus;
+        self.last_focus = (self.tab_pos, tab.parents.len(), focus);
+        if last_tab != self.tab_pos || last_depth != tab.parents.len() {
+            // a different page, nothing to move from
+            if last_tab != self.tab_pos {
+                let dir = if self.tab_pos > last_tab { 1 } else { -1 };
+                self.tab_slide.start((dir * self.width / 4) as f32, 0.0, &cfg, now);
+            }
+            self.focus_ring.jump(1.0);
+            self.shown_y.jump(self.y_offset as f32);
+        } else if last_rect != focus {
+            self.focus_from = last_rect;
+            self.focus_ring.start(0.0, 1.0, &cfg, now);
+        }
+
+        // touch scrolling already moves smoothly
+        if dragged || self.y_velocity != 0.0 {
+            self.shown_y.jump(self.y_offset as f32);
+        } else {
+            self.shown_y.set(self.y_offset as f32, &cfg, now);
+        }
+
+        self.shown_y.moving(now) || self.shown_tab_scroll.moving(now)
+            || self.tab_slide.moving(now) || self.focus_ring.moving(now)
+    }
+
+    // true if there was a tab to switch to
+    fn switch_tab(&mut self, d: i32) -> bool {
+        let new_pos = clamp(self.tab_pos as i32 + d, 0, self.max_tab_pos as i32) as usize;
+        if new_pos == self.tab_pos {
+            return false;
+        }
+        self.y_offset = 0;
+        self.tab_pos = new_pos;
+        self.tk_event_queue.push_back(TkEvent::TabChange(self.cur_tab().unwrap().id.clone()));
+        true
+    }
+    fn scroll_tabs(&mut self) {
+        let strip = self.tabs.last().map_or(0, |tab| tab.rect.unwrap().right());
+        let rect = self.cur_tab().unwrap().rect.unwrap();
+        self.tab_scroll = tab_bar_scroll(self.tab_scroll, rect, strip, self.width, self.line_height / 2);
+    }
+    fn tab_arrows(&self) -> (bool, bool) {
+        let strip = self.tabs.last().map_or(0, |tab| tab.rect.unwrap().right());
+        tab_bar_arrows(self.tab_scroll, strip, self.width)
+    }
+
+    // keeps the selection where it was on screen as long as the content allows,
+    // true if the offset changed
+    fn scroll_by(&mut self, moved: i32) -> bool {
+        let new_offset = clamp(self.y_offset - moved, self.min_offset(), 0);
+        let changed = new_offset != self.y_offset;
+        self.y_offset = new_offset;
+        changed
+    }
+
+    // opens the selected widget's page if it's a submenu
+    fn open_submenu(&mut self) -> bool {
+        let tab = &mut self.tabs[self.tab_pos];
+        let index = tab.btn_pos;
+        if tab.open_page(index, self.y_offset, &self.theme) {
+            self.y_offset = 0;
+            true
+        } else {
+            false
+        }
+    }
+    fn close_submenu(&mut self) -> Option<Id> {
+        let (submenu, y_offset) = self.tabs[self.tab_pos].close_page(&self.theme)?;
+        self.redirect_input = false;
+        // back to where the submenu was opened from, the page might've changed since
+        self.y_offset = clamp(y_offset, self.min_offset(), 0);
+        Some(submenu)
+    }
+    // goes back out of the current tab's submenu without emitting SubmenuClose,
+    // false if there's none open
+    pub fn back(&mut self) -> bool {
+        if self.close_submenu().is_some() {
+            self.dirty = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn draw(&mut self) {
+        let now = self.now();
+        let slide = self.tab_slide.value(now) as i32;
+        let y_offset = self.shown_y.value(now).round() as i32;
+        let scroll = Point::new(self.shown_tab_scroll.value(now).round() as i32, y_offset);
+        self.canvas.set_draw_color(self.theme.background);
+        self.canvas.clear();
+        for (i, tab) in self.tabs.iter_mut().enumerate() {
+            let area = Rect::new(slide, self.line_height, self.width as u32, (self.height - self.line_height) as u32);
+            tab.draw(&mut self.canvas, &self.theme, &mut self.text, self.tab_pos == i, scroll, area);
+        }
+        // an outline on its way from the last selected widget to this one
+        if self.focus_ring.moving(now) {
+            let t = self.focus_ring.value(now);
+            let to = self.last_focus.2;
+            let from = self.focus_from;
+            let lerp = |a: i32, b: i32| a + ((b - a) as f32 * t) as i32;
+            let top = self.content_top();
+            let margin = self.theme.margin as i32;
+            let ring = Rect::new(
+                lerp(from.x(), to.x()) + margin + slide,
+                lerp(from.y(), to.y()) + top + y_offset,
+                lerp(from.width() as i32, to.width() as i32) as u32,
+                lerp(from.height() as i32, to.height() as i32) as u32,
+            );
+            self.canvas.set_clip_rect(Rect::new(0, top, self.width as u32, (self.height - top) as u32));
+            self.canvas.set_draw_color(self.theme.accent);
+            self.canvas.draw_rect(ring).unwrap();
+            self.canvas.set_clip_rect(None);
+        }
+        // chevrons over the ends of the tab bar when there are more tabs that way
+        let (left, right) = self.tab_arrows();
+        let arrow = self.line_height / 2;
+        let h = self.line_height;
+        for (shown, x, dir) in [(left, 0, -1), (right, self.width - arrow, 1)] {
+            if !shown {
+                continue;
+            }
+            self.canvas.set_draw_color(self.theme.background);
+            self.canvas.fill_rect(Rect::new(x, 0, arrow as u32, h as u32)).unwrap();
+            self.canvas.set_draw_color(self.theme.foreground);
+            let (tip, back) = (x + arrow / 2 + dir * h / 8, x + arrow / 2 - dir * h / 8);
+            self.canvas.draw_line((back, h/4), (tip, h/2)).unwrap();
+            self.canvas.draw_line((tip, h/2), (back, h*3/4)).unwrap();
+        }
+        let tab = &mut self.tabs[self.tab_pos];
+        if let Some(btn) = tab.buttons.get_mut(tab.btn_pos) {
+            btn.draw_overlay(&mut self.canvas, &self.theme, &mut self.text);
+        }
+        if let Some(dialog) = self.dialogs.front_mut() {
+            dialog.draw(&mut self.canvas, &self.theme);
+        }
+    }
+
+    pub fn builder(name: &'static str) -> ToolkitBuilder {
+        ToolkitBuilder::new(name)
+    }
+
+    fn render_text(&mut self, input: &str) -> Texture {
+        let scale = ui_scale(&self.canvas);
+        self.text.render(input, scale)
+    }
+    fn render_wrapped(&mut self, input: &str) -> Texture {
+        let scale = ui_scale(&self.canvas);
+        let (width, _) = self.canvas.output_size().unwrap();
+        self.text.render_wrapped(input, scale, width.saturating_sub(self.theme.margin * 2))
+    }
+
+    // the text scale goes with the window size, so after a resize all the
+    // text is rendered again and everything laid out around it
+    fn rescale(&mut self) {
+        let padding = self.theme.padding;
+        let mut ctx = DrawContext::new(&mut self.canvas, &self.theme, &mut self.text);
+        for tab in self.tabs.iter_mut() {
+            let text = ctx.render_text(&tab.name);
+            tab.set_label(tab.name.clone(), text, padding);
+            destroy(tab.breadcrumb.take());
+            let parents = tab.parents.iter_mut().flat_map(|(_, page, _)| page.iter_mut());
+            for btn in tab.buttons.iter_mut().chain(parents) {
+                btn.rescale(&mut ctx);
+            }
+        }
+        for dialog in self.dialogs.iter_mut() {
+            dialog.rescale(&mut ctx);
+        }
+        for tab in self.tabs.iter_mut() {
+            tab.layout(&self.theme);
+        }
+        self.layout_tabs();
+    }
+
+    fn layout_tabs(&mut self) {
+        let mut offset = 0;
+        for tab in self.tabs.iter_mut() {
+            let rect = tab.rect.unwrap();
+            tab.rect = Some(Rect::new(offset, rect.y(), rect.width(), rect.height()));
+            offset += (rect.width() + self.theme.tab_spacing) as i32;
+        }
+        self.line_height = tab_bar_height(&self.tabs);
+    }
+
+    // returns false if there's no such button
+    pub fn set_button_label<S: Into<Cow<'static, str>>>(&mut self, tab: usize, button: usize, label: S) -> bool {
+        let wraps = match self.with_widget(tab, button, |btn| btn.has_label().then(|| btn.wraps())) {
+            Some(Some(wraps)) => wraps,
+            _ => return false,
+        };
+        let label = label.into();
+        let text = if wraps {
+            self.render_wrapped(&label)
+        } else {
+            self.render_text(&label)
+        };
+        let padding = self.theme.padding;
+        self.with_widget(tab, button, |btn| btn.set_label(label, text, padding));
+        self.dirty = true;
+        true
+    }
+
+    pub fn set_tab_label<S: Into<Cow<'static, str>>>(&mut self, tab: usize, label: S) -> bool {
+        if tab >= self.tabs.len() {
+            return false;
+        }
+        let label = label.into();
+        let text = self.render_text(&label);
+        let padding = self.theme.padding;
+        self.tabs[tab].set_label(label, text, padding);
+        self.layout_tabs();
+        self.dirty = true;
+        true
+    }
+
+    fn redraw(&mut self) {
+        self.draw();
+        self.canvas.present();
+    }
+
+    pub fn tab_index(&self, tab: &Id) -> Option<usize> {
+        self.tabs.iter().position(|t| t.id == *tab)
+    }
+    // (tab index, widget index), widgets inside grids, rows and submenus are
+    // counted after the ones on the tab's first page, in the order they're in,
+    // whether a submenu is open or not
+    pub fn widget_index(&self, tab: &Id, widget: &Id) -> Option<(usize, usize)> {
+        let tab = self.tab_index(tab)?;
+        let widget = self.tabs[tab].widget_ids().iter().position(|id| id == widget)?;
+        Some((tab, widget))
+    }
+    // runs `f` on the widget at the index widget_index() gives,
+    // the set_* methods go through here
+    fn with_widget<R>(&mut self, tab: usize, index: usize, f: impl FnOnce(&mut Box<dyn Buttonish>) -> R) -> Option<R> {
+        self.tabs.get_mut(tab)?.with_root(|page| {
+            let (path, _) = widget_paths(page).into_iter().nth(index)?;
+            widget_at(page, &path).map(f)
+        })
+    }
+
+    fn insert_widget(&mut self, tab: usize, index: usize, id: Id, mut btn: Box<dyn Buttonish>) -> bool {
+        btn.set_id(id);
+        let height = (btn.rect().height() + self.theme.row_spacing) as i32;
+        if let Some(t) = self.tabs.get_mut(tab) {
+            // under an open submenu, the page that's showing stays as it is
+            if let Some((open, root, _)) = t.parents.first_mut() {
+                let index = std::cmp::min(index, root.len());
+                if index <= *open {
+                    *open += 1;
+                }
+                root.insert(index, btn);
+                self.dirty = true;
+                return true;
+            }
+            let index = std::cmp::min(index, t.buttons.len());
+            // keep the selection on the same button
+            if index <= t.btn_pos && !t.buttons.is_empty() {
+                t.btn_pos += 1;
+                if tab == self.tab_pos {
+                    // the content moves, not the selection, so no animating
+                    self.y_offset -= height;
+                    self.shown_y.jump(self.y_offset as f32);
+                    self.last_focus.2.offset(0, height);
+                }
+            }
+            t.buttons.insert(index, btn);
+            t.layout(&self.theme);
+            self.dirty = true;
+            true
+        } else {
+            false
+        }
     }
-    fn cur_mut_btn(&mut self) -> Option<&mut Box<dyn Buttonish>> {
-        if let Some(tab) = self.cur_mut_tab() {
-            tab.cur_mut_btn()
+
+    // index past the end appends, always to the tab's first page
+    // even if it has a submenu open
+    pub fn insert_button<I: Into<Id>, S: Into<Cow<'static, str>>>(&mut self, tab: usize, index: usize, id: I, label: S) -> bool {
+        let label = label.into();
+        let text = self.render_text(&label);
+        self.insert_widget(tab, index, id.into(), Box::new(Button::new(label, index, text)))
+    }
+    pub fn insert_toggle<I: Into<Id>, S: Into<Cow<'static, str>>>(&mut self, tab: usize, index: usize, id: I, label: S, state: bool) -> bool {
+        let label = label.into();
+        let text = self.render_text(&label);
+        let mut toggle = Toggle::new(label, index, self.theme.padding, text);
+        toggle.state = state;
+        self.insert_widget(tab, index, id.into(), Box::new(toggle))
+    }
+    // min and max on their own like set_slider and TabBuilder::slider
+    #[allow(clippy::too_many_arguments)]
+    pub fn insert_slider<I: Into<Id>, S: Into<Cow<'static, str>>>(&mut self, tab: usize, index: usize, id: I, label: S, cur: i32, min: i32, max: i32) -> bool {
+        let label = label.into();
+        let text = self.render_text(&label);
+        let slider = Slider::new(label, text, index, self.theme.padding, cur, min, max);
+        self.insert_widget(tab, index, id.into(), Box::new(slider))
+    }
+    pub fn insert_text_input<I: Into<Id>, S: Into<Cow<'static, str>>, V: Into<String>>(&mut self, tab: usize, index: usize, id: I, label: S, value: V) -> bool {
+        let label = label.into();
+        let text = self.render_text(&label);
+        let input = TextInput::new(label, text, index, self.theme.padding, value.into(), false);
+        self.insert_widget(tab, index, id.into(), Box::new(input))
+    }
+    pub fn insert_choice<I, N, O>(&mut self, tab: usize, index: usize, id: I, label: N, options: Vec<O>, selected: usize) -> bool
+    where I: Into<Id>, N: Into<Cow<'static, str>>, O: Into<Cow<'static, str>> {
+        let label = label.into();
+        let text = self.render_text(&label);
+        let options = options.into_iter()
+            .map(|option| {
+                let option = option.into();
+                let text = self.render_text(&option);
+                (option, text)
+            })
+            .collect();
+        let choice = Choice::new(label, text, index, self.theme.padding, options, selected);
+        self.insert_widget(tab, index, id.into(), Box::new(choice))
+    }
+    pub fn insert_label<I: Into<Id>, S: Into<Cow<'static, str>>>(&mut self, tab: usize, index: usize, id: I, label: S) -> bool {
+        let label = label.into();
+        let text = self.render_text(&label);
+        self.insert_widget(tab, index, id.into(), Box::new(Label::new(label, text, false)))
+    }
+    pub fn insert_paragraph<I: Into<Id>, S: Into<Cow<'static, str>>>(&mut self, tab: usize, index: usize, id: I, text: S) -> bool {
+        let label = text.into();
+        let text = self.render_wrapped(&label);
+        self.insert_widget(tab, index, id.into(), Box::new(Label::new(label, text, true)))
+    }
+    pub fn insert_separator<I: Into<Id>>(&mut self, tab: usize, index: usize, id: I) -> bool {
+        let separator = Separator::new(self.theme.padding * 2);
+        self.insert_widget(tab, index, id.into(), Box::new(separator))
+    }
+    pub fn insert_custom<I: Into<Id>>(&mut self, tab: usize, index: usize, id: I, widget: Box<dyn Widget>) -> bool {
+        let custom = Custom::new(widget, &mut DrawContext::new(&mut self.canvas, &self.theme, &mut self.text));
+        self.insert_widget(tab, index, id.into(), Box::new(custom))
+    }
+    // measures a custom widget again and redraws on the next tick, call it after changing
+    // anything that the widget draws, false if it's not a custom widget
+    pub fn update_widget(&mut self, tab: usize, index: usize) -> bool {
+        if tab >= self.tabs.len() {
+            return false;
+        }
+        let mut ctx = DrawContext::new(&mut self.canvas, &self.theme, &mut self.text);
+        let t = &mut self.tabs[tab];
+        let ok = t.with_root(|page| {
+            let (path, _) = widget_paths(page).into_iter().nth(index)?;
+            widget_at(page, &path).map(|btn| btn.measure(&mut ctx))
+        }) == Some(true);
+        t.layout(ctx.theme());
+        if ok {
+            self.dirty = true;
+        }
+        ok
+    }
+    pub fn insert_image<I: Into<Id>>(&mut self, tab: usize, index: usize, id: I, image: &Image) -> bool {
+        let picture = Picture::new(image, self.text.creator(), ui_scale(&self.canvas));
+        self.insert_widget(tab, index, id.into(), Box::new(picture))
+    }
+    // false if there's no such button, it's not a plain button
+    // or the icon can't be made into a texture
+    pub fn set_button_icon(&mut self, tab: usize, index: usize, icon: &Image) -> bool {
+        let texture = match icon.texture(self.text.creator()) {
+            Ok(texture) => texture,
+            Err(_) => return false,
+        };
+        let padding = self.theme.padding;
+        let ok = self.with_widget(tab, index, |btn| btn.set_icon(texture, padding)) == Some(true);
+        if ok {
+            self.dirty = true;
+        }
+        ok
+    }
+    pub fn set_tab_icon(&mut self, tab: usize, icon: &Image) -> bool {
+        if tab >= self.tabs.len() {
+            return false;
+        }
+        let texture = match icon.texture(self.text.creator()) {
+            Ok(texture) => texture,
+            Err(_) => return false,
+        };
+        self.tabs[tab].set_icon(texture, self.theme.padding);
+        self.layout_tabs();
+        self.dirty = true;
+        true
+    }
+    // from the tab's first page like the insert_* methods
+    pub fn remove_button(&mut self, tab: usize, index: usize) -> bool {
+        let open = match self.tabs.get(tab) {
+            Some(t) if index < t.parents.first().map_or(t.buttons.len(), |(_, root, _)| root.len()) => {
+                t.parents.first().map(|(open, _, _)| *open)
+            },
+            _ => return false,
+        };
+        // submenus opened from it close along with it
+        if open == Some(index) {
+            if tab == self.tab_pos {
+                while self.close_submenu().is_some() {}
+            } else {
+                while self.tabs[tab].close_page(&self.theme).is_some() {}
+            }
+        }
+        if tab == self.tab_pos && index == self.tabs[tab].btn_pos && self.tabs[tab].parents.is_empty() {
+            self.redirect_input = false;
+        }
+        let t = &mut self.tabs[tab];
+        if let Some((open, root, _)) = t.parents.first_mut() {
+            // laid out again when the submenu closes
+            let btn = root.remove(index);
+            t.callbacks.remove(btn.id());
+            if index < *open {
+                *open -= 1;
+            }
         } else {
-            None
+            let btn = t.buttons.remove(index);
+            t.callbacks.remove(btn.id());
+            if index < t.btn_pos {
+                t.btn_pos -= 1;
+            }
+            t.layout(&self.theme);
+        }
+        self.dirty = true;
+        true
+    }
+    pub fn set_toggle(&mut self, tab: usize, index: usize, state: bool) -> bool {
+        let ok = self.with_widget(tab, index, |btn| btn.set_state(state)) == Some(true);
+        if ok {
+            self.dirty = true;
+        }
+        ok
+    }
+    pub fn set_slider(&mut self, tab: usize, index: usize, level: i32, min: i32, max: i32) -> bool {
+        let ok = self.with_widget(tab, index, |btn| btn.set_level(level, min, max)) == Some(true);
+        if ok {
+            self.dirty = true;
+        }
+        ok
+    }
+
+    pub fn set_text<V: AsRef<str>>(&mut self, tab: usize, index: usize, value: V) -> bool {
+        let ok = self.with_widget(tab, index, |btn| btn.set_text(value.as_ref())) == Some(true);
+        if ok {
+            self.dirty = true;
+        }
+        ok
+    }
+    // false if the index is past the last option
+    pub fn set_choice(&mut self, tab: usize, index: usize, selected: usize) -> bool {
+        let ok = self.with_widget(tab, index, |btn| btn.set_choice(selected)) == Some(true);
+        if ok {
+            self.dirty = true;
+        }
+        ok
+    }
+
+    // replaces the widget's callback if it already had one
+    pub fn set_callback<F: FnMut(&mut Context) + 'static>(&mut self, tab: usize, index: usize, callback: F) -> bool {
+        if let Some(t) = self.tabs.get_mut(tab) {
+            if let Some(id) = t.widget_ids().into_iter().nth(index) {
+                t.callbacks.insert(id, Box::new(callback));
+                return true;
+            }
+        }
+        false
+    }
+
+    // switches tabs without emitting TabChange
+    pub fn set_tab(&mut self, index: usize) -> bool {
+        if index >= self.tabs.len() {
+            return false;
+        }
+        if index != self.tab_pos {
+            self.tab_pos = index;
+            self.redirect_input = false;
+            self.y_offset = 0;
+            self.dirty = true;
+        }
+        true
+    }
+    // see ToolkitBuilder::animations, anything that's moving stops where it's going
+    pub fn set_animations(&mut self, enabled: bool) {
+        self.animation.enabled = enabled;
+        if !enabled {
+            self.y_velocity = 0.0;
+            self.shown_y.jump(self.y_offset as f32);
+            self.shown_tab_scroll.jump(self.tab_scroll as f32);
+            self.tab_slide.jump(0.0);
+            self.focus_ring.jump(1.0);
+        }
+    }
+    // tick() returns false from now on
+    pub fn quit(&mut self) {
+        self.run = false;
+    }
+
+    // Modal dialogs are drawn over the current tab and take all input until
+    // they're answered, each one waits for the ones opened before it.
+    // Returns the id the DialogResult will carry.
+    fn open_dialog(&mut self, kind: DialogKind, message: &str, buttons: &[&str]) -> Id {
+        let id = Id::unique();
+        let message = (message.to_string(), self.render_text(message));
+        let buttons = buttons.iter().map(|label| (label.to_string(), self.render_text(label))).collect();
+        self.dialogs.push_back(Dialog::new(id.clone(), kind, message, buttons));
+        self.dirty = true;
+        id
+    }
+    pub fn confirm<S: AsRef<str>>(&mut self, message: S) -> Id {
+        self.open_dialog(DialogKind::Confirm, message.as_ref(), &["Yes", "No"])
+    }
+    // the callback runs in place of the DialogResult event
+    pub fn confirm_with<S: AsRef<str>, F: FnMut(&mut Context) + 'static>(&mut self, message: S, callback: F) -> Id {
+        let id = self.confirm(message);
+        self.dialog_callbacks.insert(id.clone(), Box::new(callback));
+        id
+    }
+    pub fn alert<S: AsRef<str>>(&mut self, message: S) -> Id {
+        self.open_dialog(DialogKind::Alert, message.as_ref(), &["OK"])
+    }
+    // can't be dismissed, close it with close_dialog once done
+    pub fn progress<S: AsRef<str>>(&mut self, message: S) -> Id {
+        self.open_dialog(DialogKind::Progress(0.0), message.as_ref(), &[])
+    }
+    // 0.0 to 1.0
+    pub fn set_progress(&mut self, dialog: &Id, progress: f32) -> bool {
+        let ok = self.dialogs.iter_mut()
+            .find(|d| d.id == *dialog)
+            .is_some_and(|d| d.set_progress(progress));
+        if ok {
+            self.dirty = true;
+        }
+        ok
+    }
+    // closes any dialog without a DialogResult
+    pub fn close_dialog(&mut self, dialog: &Id) -> bool {
+        let len = self.dialogs.len();
+        self.dialogs.retain(|d| d.id != *dialog);
+        self.dialog_callbacks.remove(dialog);
+        if self.dialogs.len() == len {
+            return false;
         }
+        self.dirty = true;
+        true
     }
-    fn cur_btn(&self) -> Option<&Box<dyn Buttonish>> {
-        if let Some(tab) = self.tabs.get(self.tab_pos) {
-            tab.cur_btn()
+
+    pub fn insert_tab<I: Into<Id>, S: Into<Cow<'static, str>>>(&mut self, index: usize, id: I, label: S) {
+        let label = label.into();
+        let text = self.render_text(&label);
+        let index = std::cmp::min(index, self.tabs.len());
+        let mut tab = Tab::new(label, Vec::new(), text, 0, &self.theme);
+        tab.id = id.into();
+        self.tabs.insert(index, tab);
+        if index <= self.tab_pos {
+            self.tab_pos += 1;
+        }
+        self.max_tab_pos = self.tabs.len() - 1;
+        self.layout_tabs();
+        self.dirty = true;
+    }
+    // there has to be at least one tab left
+    pub fn remove_tab(&mut self, index: usize) -> bool {
+        if index >= self.tabs.len() || self.tabs.len() == 1 {
+            return false;
+        }
+        self.tabs.remove(index);
+        if index == self.tab_pos {
+            self.redirect_input = false;
+            self.y_offset = 0;
+        }
+        if index < self.tab_pos || self.tab_pos >= self.tabs.len() {
+            self.tab_pos -= 1;
+        }
+        self.max_tab_pos = self.tabs.len() - 1;
+        self.layout_tabs();
+        self.dirty = true;
+        true
+    }
+
+    pub fn poll_events(&mut self) -> Option<TkEvent> {
+        self.tk_event_queue.pop_front()
+    }
+
+    // synthetic input, goes through the same queue as real input and
+    // gets handled on the next tick()
+    pub fn press(&mut self, action: InputAction) {
+        if let Some(ev) = action.to_event(true) {
+            self.event_sender.push_custom_event(ev).unwrap();
+        }
+    }
+    pub fn release(&mut self, action: InputAction) {
+        if let Some(ev) = action.to_event(false) {
+            self.event_sender.push_custom_event(ev).unwrap();
+        }
+    }
+    pub fn inject(&mut self, action: InputAction) {
+        self.press(action);
+        self.release(action);
+    }
+    pub fn inject_touch(&mut self, x: i32, y: i32) {
+        self.event_sender.push_custom_event(InternalTkEvent::TouchPress(x, y)).unwrap();
+    }
+
+    fn now(&self) -> Instant {
+        self.clock.unwrap_or_else(|| Instant::now() + self.clock_ahead)
+    }
+
+    fn cur_mut_tab(&mut self) -> Option<&mut Tab> {
+        self.tabs.get_mut(self.tab_pos)
+    }
+    fn cur_tab(&self) -> Option<&Tab> {
+        self.tabs.get(self.tab_pos)
+    }
+    fn cur_mut_btn(&mut self) -> Option<&mut Box<dyn Buttonish>> {
+        if let Some(tab) = self.cur_mut_tab() {
+            tab.cur_mut_btn()
         } else {
             None
         }
@@ -533,149 +2405,568 @@ impl Toolkit {
 }
 
 // Input:
-fn handle_inputs(sdl_tx: EventSender) {
-    loop {
-        thread::sleep(std::time::Duration::from_secs(1));
-        sdl_tx.push_custom_event(InternalTkEvent::Dummy).unwrap();
-    }
-    // it's much faster to use raw linux evdev apis
-    // than sdl2 gamecontroller api
-    // find rinputer(or close enough) device:
-    // open it and convert events into sdl input events:
-}
+mod input;
+use input::{handle_inputs, DeviceConfig, Repeater, RepeatConfig};
+mod input_map;
+pub use input_map::{InputMap, InputAction};
+
+mod font;
+pub use font::FontConfig;
+use font::TextRenderer;
+mod theme;
+pub use theme::Theme;
+mod keyboard;
+use keyboard::{Keyboard, KeyboardState};
+mod dialog;
+use dialog::{Dialog, DialogKind};
+mod image;
+pub use image::Image;
+mod widget;
+pub use widget::{Widget, WidgetInput, Response, DrawContext};
+// custom widgets draw with sdl2 directly, this way they get the same version
+pub use sdl2;
+use widget::Custom;
+mod container;
+use container::Container;
+mod animation;
+pub use animation::Easing;
+use animation::{AnimationConfig, Tween};
+
+// Testing:
+mod headless;
+pub use headless::Step;
 
 
 // Initialization:
 
+// older sdl versions only look at the environment variable when the video subsystem
+// starts, it's process wide so the caller's choice is put back afterwards
+fn with_video_driver<T>(driver: &str, f: impl FnOnce() -> T) -> T {
+    let saved = env::var_os("SDL_VIDEODRIVER");
+    env::set_var("SDL_VIDEODRIVER", driver);
+    let result = f();
+    match saved {
+        Some(saved) => env::set_var("SDL_VIDEODRIVER", saved),
+        None => env::remove_var("SDL_VIDEODRIVER"),
+    }
+    result
+}
+
 pub struct ToolkitBuilder {
     name: &'static str,
     tabs: Vec<Tab>,
-    ttf: sdl2::ttf::Sdl2TtfContext,
+    text: TextRenderer,
+    theme: Theme,
     canvas: Canvas<sdl2::video::Window>,
-    text_creator: sdl2::render::TextureCreator<sdl2::video::WindowContext>,
     event_pump: sdl2::EventPump,
     event_sender: EventSender,
     event_sender_2: Option<EventSender>,
     newtab_offset: u32,
+    deadzone: f32,
+    repeat: RepeatConfig,
+    animation: AnimationConfig,
+    input_map: InputMap,
+    controller_subsystem: GameControllerSubsystem,
+    headless: bool,
+    window: WindowOptions,
+    window_error: Option<String>,
+    font_error: Option<String>,
 }
 
 impl ToolkitBuilder {
     pub fn new(name: &'static str) -> ToolkitBuilder {
-        let sdl2_ctx = sdl2::init().unwrap();
-        let video = sdl2_ctx.video().unwrap();
-        let window = video.window(name, 640, 480).build().unwrap();
-        let canvas = window.into_canvas().present_vsync().build().unwrap();
-        let text_creator = canvas.texture_creator();
-
-        let ev = sdl2_ctx.event().unwrap();
-        ev.register_custom_event::<InternalTkEvent>().unwrap();
+        ToolkitBuilder::try_new(name).unwrap_or_else(|e| panic!("gamepad_gui: {}", e))
+    }
+    // renders into a hidden window on sdl's dummy video driver with the software
+    // renderer, doesn't need a display and doesn't read any real gamepads
+    pub fn headless(name: &'static str) -> ToolkitBuilder {
+        ToolkitBuilder::try_headless(name).unwrap_or_else(|e| panic!("gamepad_gui: {}", e))
+    }
+    // like new() and headless(), but with an error if there's no window
+    // or no text rendering
+    pub fn try_new(name: &'static str) -> Result<ToolkitBuilder, String> {
+        ToolkitBuilder::init(name, false)
+    }
+    pub fn try_headless(name: &'static str) -> Result<ToolkitBuilder, String> {
+        ToolkitBuilder::init(name, true)
+    }
+    fn init(name: &'static str, headless: bool) -> Result<ToolkitBuilder, String> {
+        let sdl2_ctx = sdl2::init()?;
+        let video = if headless {
+            with_video_driver("dummy", || sdl2_ctx.video())?
+        } else {
+            match sdl2_ctx.video() {
+                Ok(video) => video,
+                // straight from init there's no X11 or wayland to fall back from,
+                // sdl only tries kmsdrm on its own in some builds
+                Err(e) if env::var_os("SDL_VIDEODRIVER").is_none() => {
+                    eprintln!("gamepad_gui: no video driver ({}), trying kmsdrm", e);
+                    with_video_driver("kmsdrm", || sdl2_ctx.video())?
+                },
+                Err(e) => return Err(e),
+            }
+        };
+        let canvas = if headless {
+            let window = video.window(name, 640, 480).hidden().build().map_err(|e| e.to_string())?;
+            window.into_canvas().software().build().map_err(|e| e.to_string())?
+        } else {
+            // kmsdrm can only do fullscreen at the panel's native mode
+            let make_window = || if video.current_video_driver() == "KMSDRM" {
+                let mode = video.desktop_display_mode(0)?;
+                video.window(name, mode.w as u32, mode.h as u32).fullscreen().build().map_err(|e| e.to_string())
+            } else {
+                video.window(name, 640, 480).resizable().build().map_err(|e| e.to_string())
+            };
+            // no gles on some boards, the software renderer is always there
+            match make_window()?.into_canvas().present_vsync().build() {
+                Ok(canvas) => canvas,
+                Err(e) => {
+                    eprintln!("gamepad_gui: accelerated renderer failed ({}), using software", e);
+                    make_window()?.into_canvas().software().build().map_err(|e| e.to_string())?
+                },
+            }
+        };
+        let text = TextRenderer::new(canvas.texture_creator(), ui_scale(&canvas))?;
+
+        let ev = sdl2_ctx.event()?;
+        // sdl2 remembers the event type for the whole process, later toolkits
+        // (like one per test) would get an error for registering it again
+        static REGISTERED: OnceLock<Result<(), String>> = OnceLock::new();
+        REGISTERED.get_or_init(|| ev.register_custom_event::<InternalTkEvent>()).clone()?;
         let event_sender = ev.event_sender();
         let event_sender_2 = Some(ev.event_sender());
-        let event_pump = sdl2_ctx.event_pump().unwrap();
+        let event_pump = sdl2_ctx.event_pump()?;
+        let controller_subsystem = sdl2_ctx.game_controller()?;
 
-        ToolkitBuilder {
-            ttf: sdl2::ttf::init().unwrap(),
+        Ok(ToolkitBuilder {
+            text,
+            theme: Theme::default(),
             canvas,
-            text_creator,
             name,
             event_pump,
             event_sender,
             event_sender_2,
             newtab_offset: 0,
             tabs: Vec::new(),
+            deadzone: 0.25,
+            repeat: RepeatConfig::default(),
+            animation: AnimationConfig::default(),
+            input_map: InputMap::default(),
+            controller_subsystem,
+            headless,
+            window: WindowOptions::default(),
+            window_error: None,
+            font_error: None,
+        })
+    }
+    pub fn fullscreen(mut self) -> ToolkitBuilder {
+        self.window.fullscreen = Some(FullscreenType::True);
+        self
+    }
+    // fullscreen without changing the display mode
+    pub fn fullscreen_desktop(mut self) -> ToolkitBuilder {
+        self.window.fullscreen = Some(FullscreenType::Desktop);
+        self
+    }
+    pub fn display(mut self, index: i32) -> ToolkitBuilder {
+        self.window.display = Some(index);
+        self
+    }
+    pub fn hide_cursor(self) -> ToolkitBuilder {
+        self.canvas.window().subsystem().sdl().mouse().show_cursor(false);
+        self
+    }
+    pub fn size(mut self, width: u32, height: u32) -> ToolkitBuilder {
+        self.window.size = Some((width, height));
+        self
+    }
+    pub fn theme(mut self, theme: Theme) -> ToolkitBuilder {
+        self.theme = theme;
+        self
+    }
+    // a config without any usable font is an error from try_build()
+    pub fn font(mut self, config: FontConfig) -> ToolkitBuilder {
+        let scale = ui_scale(&self.canvas);
+        if let Err(e) = self.text.set_config(config, scale) {
+            self.font_error = Some(e);
         }
+        self
+    }
+    pub fn input_map(mut self, map: InputMap) -> ToolkitBuilder {
+        self.input_map = map;
+        self
+    }
+    // fraction of the stick's travel that's ignored, 0.0 to 1.0
+    pub fn stick_deadzone(mut self, deadzone: f32) -> ToolkitBuilder {
+        self.deadzone = clamp(deadzone, 0.0, 1.0);
+        self
+    }
+    // wait `delay` before repeating a held direction every `interval`,
+    // then speed up until repeating every `fastest`
+    pub fn key_repeat(mut self, delay: Duration, interval: Duration, fastest: Duration) -> ToolkitBuilder {
+        self.repeat = RepeatConfig {
+            delay,
+            interval,
+            fastest,
+        };
+        self
+    }
+    // smooth scrolling, sliding tabs and the moving selection outline,
+    // turn them off on slow devices to only redraw when something changes
+    pub fn animations(mut self, enabled: bool) -> ToolkitBuilder {
+        self.animation.enabled = enabled;
+        self
+    }
+    // how long scrolling and the other transitions take and how they speed up and slow down
+    pub fn animation_style(mut self, duration: Duration, easing: Easing) -> ToolkitBuilder {
+        self.animation.duration = duration;
+        self.animation.easing = easing;
+        self
     }
-    pub fn tab(self, name: &'static str) -> TabBuilder {
+    pub fn tab<S: Into<Cow<'static, str>>>(mut self, name: S) -> TabBuilder {
+        // text is scaled along with the window, so the window has to be set up
+        // before the first tab renders anything
+        if self.tabs.is_empty() {
+            self.window_error = self.apply_window().err();
+        }
         TabBuilder {
-            name,
+            name: name.into(),
+            id: None,
             buttons: Vec::new(),
+            callbacks: HashMap::new(),
+            icon: None,
+            nesting: Vec::new(),
             builder: self,
         }
     }
-    fn render_text(&mut self, input: &'static str) -> Texture {
-        let font = self.ttf.load_font("/usr/share/fonts/liberation/LiberationSans-Regular.ttf", 28).unwrap();
-        let surface = font.render(input).blended(Color::RGBA(255, 255, 255, 255)).unwrap();
-        self.text_creator.create_texture_from_surface(&surface).unwrap()
+    // a failing option comes back from try_build()
+    fn apply_window(&mut self) -> Result<(), String> {
+        let options = self.window;
+        let window = self.canvas.window_mut();
+        if let Some((width, height)) = options.size {
+            window.set_size(width, height).map_err(|e| e.to_string())?;
+        }
+        if let Some(index) = options.display {
+            let bounds = window.subsystem().display_bounds(index)?;
+            // can't move fullscreen windows around
+            let fullscreen = window.fullscreen_state();
+            window.set_fullscreen(FullscreenType::Off)?;
+            let (w, h) = window.size();
+            window.set_position(
+                WindowPos::Positioned(bounds.x() + (bounds.width() as i32 - w as i32) / 2),
+                WindowPos::Positioned(bounds.y() + (bounds.height() as i32 - h as i32) / 2),
+            );
+            window.set_fullscreen(fullscreen)?;
+        }
+        if let Some(fullscreen) = options.fullscreen {
+            window.set_fullscreen(fullscreen)?;
+        }
+        Ok(())
+    }
+    fn render_text(&mut self, input: &str) -> Texture {
+        let scale = ui_scale(&self.canvas);
+        self.text.render(input, scale)
+    }
+    fn render_wrapped(&mut self, input: &str) -> Texture {
+        let scale = ui_scale(&self.canvas);
+        let (width, _) = self.canvas.output_size().unwrap();
+        self.text.render_wrapped(input, scale, width.saturating_sub(self.theme.margin * 2))
     }
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+struct WindowOptions {
+    fullscreen: Option<FullscreenType>,
+    display: Option<i32>,
+    size: Option<(u32, u32)>,
+}
+
+enum Nesting {
+    Submenu(Cow<'static, str>),
+    // 0 columns is a row
+    Grid(usize),
+}
+
 pub struct TabBuilder {
-    name: &'static str,
+    name: Cow<'static, str>,
+    id: Option<Id>,
     buttons: Vec<Box<dyn Buttonish>>,
+    callbacks: HashMap<Id, Callback>,
+    icon: Option<Texture>,
+    // submenus, grids and rows that are still being filled in,
+    // with the pages they go into
+    nesting: Vec<(Nesting, Page)>,
     builder: ToolkitBuilder,
 }
 
 impl TabBuilder {
-    pub fn button(mut self, name: &'static str) -> TabBuilder {
-        let text = self.builder.render_text(name);
+    pub fn button<S: Into<Cow<'static, str>>>(mut self, name: S) -> TabBuilder {
+        let name = name.into();
+        let text = self.builder.render_text(&name);
         self.buttons.push(Box::new(Button::new(name, self.buttons.len(), text)));
         self
     }
-    pub fn toggle(mut self, name: &'static str) -> TabBuilder {
-        let text = self.builder.render_text(name);
-        self.buttons.push(Box::new(Toggle::new(name, self.buttons.len(), text)));
+    pub fn toggle<S: Into<Cow<'static, str>>>(mut self, name: S) -> TabBuilder {
+        let name = name.into();
+        let text = self.builder.render_text(&name);
+        let padding = self.builder.theme.padding;
+        self.buttons.push(Box::new(Toggle::new(name, self.buttons.len(), padding, text)));
+        self
+    }
+    pub fn slider<S: Into<Cow<'static, str>>>(mut self, name: S, cur: i32, min: i32, max: i32) -> TabBuilder {
+        let name = name.into();
+        let text = self.builder.render_text(&name);
+        let padding = self.builder.theme.padding;
+        self.buttons.push(Box::new(Slider::new(name, text, self.buttons.len(), padding, cur, min, max)));
+        self
+    }
+    pub fn text_input<S: Into<Cow<'static, str>>, V: Into<String>>(mut self, name: S, value: V) -> TabBuilder {
+        let name = name.into();
+        let text = self.builder.render_text(&name);
+        let padding = self.builder.theme.padding;
+        self.buttons.push(Box::new(TextInput::new(name, text, self.buttons.len(), padding, value.into(), false)));
+        self
+    }
+    pub fn widget(mut self, widget: Box<dyn Widget>) -> TabBuilder {
+        let builder = &mut self.builder;
+        let custom = Custom::new(widget, &mut DrawContext::new(&mut builder.canvas, &builder.theme, &mut builder.text));
+        self.buttons.push(Box::new(custom));
+        self
+    }
+    // not selectable, for headers and such
+    pub fn label<S: Into<Cow<'static, str>>>(mut self, name: S) -> TabBuilder {
+        let name = name.into();
+        let text = self.builder.render_text(&name);
+        self.buttons.push(Box::new(Label::new(name, text, false)));
+        self
+    }
+    // a label over as many lines as it takes
+    pub fn paragraph<S: Into<Cow<'static, str>>>(mut self, text: S) -> TabBuilder {
+        let name = text.into();
+        let text = self.builder.render_wrapped(&name);
+        self.buttons.push(Box::new(Label::new(name, text, true)));
+        self
+    }
+    pub fn separator(mut self) -> TabBuilder {
+        let padding = self.builder.theme.padding;
+        self.buttons.push(Box::new(Separator::new(padding * 2)));
+        self
+    }
+    // scaled along with the text
+    pub fn image(mut self, image: &Image) -> TabBuilder {
+        let scale = ui_scale(&self.builder.canvas);
+        self.buttons.push(Box::new(Picture::new(image, self.builder.text.creator(), scale)));
+        self
+    }
+    // puts an icon before the last added button's label,
+    // the button goes without if the icon can't be made into a texture
+    pub fn icon(mut self, icon: &Image) -> TabBuilder {
+        let texture = match icon.texture(self.builder.text.creator()) {
+            Ok(texture) => texture,
+            Err(e) => {
+                eprintln!("gamepad_gui: can't show icon, {}", e);
+                return self;
+            },
+        };
+        let padding = self.builder.theme.padding;
+        if let Some(btn) = self.buttons.last_mut() {
+            btn.set_icon(texture, padding);
+        }
+        self
+    }
+    pub fn tab_icon(mut self, icon: &Image) -> TabBuilder {
+        self.icon = icon.texture(self.builder.text.creator())
+            .map_err(|e| eprintln!("gamepad_gui: can't show icon, {}", e))
+            .ok();
+        self
+    }
+    // cycles through the options with left/right after being pressed
+    pub fn choice<N, O>(mut self, name: N, options: Vec<O>, selected: usize) -> TabBuilder
+    where N: Into<Cow<'static, str>>, O: Into<Cow<'static, str>> {
+        let name = name.into();
+        let text = self.builder.render_text(&name);
+        let options = options.into_iter()
+            .map(|option| {
+                let option = option.into();
+                let text = self.builder.render_text(&option);
+                (option, text)
+            })
+            .collect();
+        let padding = self.builder.theme.padding;
+        self.buttons.push(Box::new(Choice::new(name, text, self.buttons.len(), padding, options, selected)));
+        self
+    }
+    // text input that only ever shows asterisks
+    pub fn password_input<S: Into<Cow<'static, str>>>(mut self, name: S) -> TabBuilder {
+        let name = name.into();
+        let text = self.builder.render_text(&name);
+        let padding = self.builder.theme.padding;
+        self.buttons.push(Box::new(TextInput::new(name, text, self.buttons.len(), padding, String::new(), true)));
+        self
+    }
+    // same as the plain versions, but the callback runs in place of
+    // the widget's event
+    pub fn button_with<S, F>(self, name: S, callback: F) -> TabBuilder
+    where S: Into<Cow<'static, str>>, F: FnMut(&mut Context) + 'static {
+        self.button(name).callback(callback)
+    }
+    pub fn toggle_with<S, F>(self, name: S, callback: F) -> TabBuilder
+    where S: Into<Cow<'static, str>>, F: FnMut(&mut Context) + 'static {
+        self.toggle(name).callback(callback)
+    }
+    pub fn slider_with<S, F>(self, name: S, cur: i32, min: i32, max: i32, callback: F) -> TabBuilder
+    where S: Into<Cow<'static, str>>, F: FnMut(&mut Context) + 'static {
+        self.slider(name, cur, min, max).callback(callback)
+    }
+    pub fn text_input_with<S, V, F>(self, name: S, value: V, callback: F) -> TabBuilder
+    where S: Into<Cow<'static, str>>, V: Into<String>, F: FnMut(&mut Context) + 'static {
+        self.text_input(name, value).callback(callback)
+    }
+    pub fn choice_with<N, O, F>(self, name: N, options: Vec<O>, selected: usize, callback: F) -> TabBuilder
+    where N: Into<Cow<'static, str>>, O: Into<Cow<'static, str>>, F: FnMut(&mut Context) + 'static {
+        self.choice(name, options, selected).callback(callback)
+    }
+    // everything added until the matching end_submenu() goes into a page
+    // that the submenu button opens, Back closes it again
+    pub fn submenu<S: Into<Cow<'static, str>>>(mut self, name: S) -> TabBuilder {
+        let parent = std::mem::take(&mut self.buttons);
+        self.nesting.push((Nesting::Submenu(name.into()), parent));
+        self
+    }
+    pub fn end_submenu(mut self) -> TabBuilder {
+        if let Some((Nesting::Submenu(_), _)) = self.nesting.last() {
+            let (name, parent) = match self.nesting.pop() {
+                Some((Nesting::Submenu(name), parent)) => (name, parent),
+                _ => unreachable!(),
+            };
+            let page = std::mem::replace(&mut self.buttons, parent);
+            let text = self.builder.render_text(&name);
+            let padding = self.builder.theme.padding;
+            self.buttons.push(Box::new(Submenu::new(name, self.buttons.len(), padding, text, page)));
+        }
+        self
+    }
+    // everything added until the matching end_grid() is laid out in
+    // `columns` equally sized cells per row, for tile menus
+    pub fn grid(mut self, columns: usize) -> TabBuilder {
+        let parent = std::mem::take(&mut self.buttons);
+        self.nesting.push((Nesting::Grid(std::cmp::max(columns, 1)), parent));
+        self
+    }
+    pub fn end_grid(mut self) -> TabBuilder {
+        if let Some((Nesting::Grid(columns), _)) = self.nesting.last() {
+            let columns = *columns;
+            let (_, parent) = self.nesting.pop().unwrap();
+            let children = std::mem::replace(&mut self.buttons, parent);
+            self.buttons.push(Box::new(Container::new(children, columns, self.builder.theme.padding)));
+        }
+        self
+    }
+    // a grid with everything on one line
+    pub fn row(mut self) -> TabBuilder {
+        let parent = std::mem::take(&mut self.buttons);
+        self.nesting.push((Nesting::Grid(0), parent));
+        self
+    }
+    pub fn end_row(self) -> TabBuilder {
+        self.end_grid()
+    }
+    fn end_all(mut self) -> TabBuilder {
+        while let Some((nesting, _)) = self.nesting.last() {
+            self = match nesting {
+                Nesting::Submenu(_) => self.end_submenu(),
+                Nesting::Grid(_) => self.end_grid(),
+            };
+        }
+        self
+    }
+    // sets the callback of the last added widget
+    pub fn callback<F: FnMut(&mut Context) + 'static>(mut self, callback: F) -> TabBuilder {
+        if let Some(btn) = self.buttons.last() {
+            self.callbacks.insert(btn.id().clone(), Box::new(callback));
+        }
+        self
+    }
+    // sets the id of the last added widget
+    pub fn with_id<I: Into<Id>>(mut self, id: I) -> TabBuilder {
+        if let Some(btn) = self.buttons.last_mut() {
+            let id = id.into();
+            if let Some(callback) = self.callbacks.remove(btn.id()) {
+                self.callbacks.insert(id.clone(), callback);
+            }
+            btn.set_id(id);
+        }
         self
     }
-    pub fn slider(mut self, name: &'static str, cur: i32, min: i32, max: i32) -> TabBuilder {
-        let text = self.builder.render_text(name);
-        self.buttons.push(Box::new(Slider::new(name, text, self.buttons.len(), cur, min, max)));
+    pub fn tab_id<I: Into<Id>>(mut self, id: I) -> TabBuilder {
+        self.id = Some(id.into());
         self
     }
-    pub fn buttons_vec(mut self, names: Vec<&'static str>) -> TabBuilder {
+    pub fn buttons_vec<S: Into<Cow<'static, str>>>(mut self, names: Vec<S>) -> TabBuilder {
         for name in names {
-            let text = self.builder.render_text(name);
+            let name = name.into();
+            let text = self.builder.render_text(&name);
             self.buttons.push(Box::new(Button::new(name, self.buttons.len(), text)));
         }
         self
     }
-    pub fn tab(mut self, name: &'static str) -> TabBuilder {
-        let max_btn_pos = self.buttons.len() - 1;
-        let text = self.builder.render_text(self.name);
-        let attr = text.query();
-        let rect = Rect::new(self.builder.newtab_offset as i32, 0, attr.width, attr.height);
-        self.builder.newtab_offset += attr.width + 10;
-        self.builder.tabs.push(Tab {
-            name: self.name,
-            buttons: self.buttons,
-            btn_pos: 0,
-            max_btn_pos,
-            text: Some(text),
-            rect: Some(rect),
-        });
+    pub fn tab<S: Into<Cow<'static, str>>>(mut self, name: S) -> TabBuilder {
+        self = self.end_all();
+        let text = self.builder.render_text(&self.name);
+        let mut tab = Tab::new(self.name, self.buttons, text, self.builder.newtab_offset as i32, &self.builder.theme);
+        if let Some(id) = self.id {
+            tab.id = id;
+        }
+        tab.callbacks = self.callbacks;
+        if let Some(icon) = self.icon {
+            tab.set_icon(icon, self.builder.theme.padding);
+        }
+        self.builder.newtab_offset += tab.rect.unwrap().width() + self.builder.theme.tab_spacing;
+        self.builder.tabs.push(tab);
         self.builder.tab(name)
     }
-    pub fn build(mut self) -> Toolkit {
-        let max_btn_pos = self.buttons.len() - 1;
-        let text = self.builder.render_text(self.name);
-        let attr = text.query();
-        let rect = Rect::new(self.builder.newtab_offset as i32, 0, attr.width, attr.height);
-        self.builder.newtab_offset += attr.width + 10;
+    pub fn build(self) -> Toolkit {
+        self.try_build().unwrap_or_else(|e| panic!("gamepad_gui: {}", e))
+    }
+    // like build(), but with an error if the window or font options can't be applied
+    pub fn try_build(mut self) -> Result<Toolkit, String> {
+        self = self.end_all();
+        let text = self.builder.render_text(&self.name);
+        let mut tab = Tab::new(self.name, self.buttons, text, self.builder.newtab_offset as i32, &self.builder.theme);
+        if let Some(id) = self.id {
+            tab.id = id;
+        }
+        tab.callbacks = self.callbacks;
+        if let Some(icon) = self.icon {
+            tab.set_icon(icon, self.builder.theme.padding);
+        }
+        self.builder.newtab_offset += tab.rect.unwrap().width() + self.builder.theme.tab_spacing;
+        self.builder.tabs.push(tab);
 
-        self.builder.tabs.push(Tab {
-            name: self.name,
-            buttons: self.buttons,
-            btn_pos: 0,
-            max_btn_pos,
-            text: Some(text),
-            rect: Some(rect),
-        });
+        if let Some(e) = self.builder.font_error.take() {
+            return Err(e);
+        }
+        if let Some(e) = self.builder.window_error.take() {
+            return Err(e);
+        }
 
-        if let Some(sender) = self.builder.event_sender_2.take() {
-            thread::spawn(move || handle_inputs(sender));
+        if self.builder.headless {
+            self.builder.event_sender_2 = None;
+        } else if let Some(sender) = self.builder.event_sender_2.take() {
+            let cfg = DeviceConfig {
+                deadzone: self.builder.deadzone,
+                bindings: self.builder.input_map.evdev.clone(),
+            };
+            thread::spawn(move || handle_inputs(sender, cfg));
         } else {
             unreachable!();
         }
 
         let max_tab_pos = self.builder.tabs.len() - 1;
+        let line_height = tab_bar_height(&self.builder.tabs);
+        let (width, height) = self.builder.canvas.output_size().unwrap();
 
-        self.builder.canvas.set_draw_color(Color::RGB(0, 0, 0));
-        self.builder.canvas.clear();
-        for (i, tab) in self.builder.tabs.iter_mut().enumerate() {
-            tab.draw(&mut self.builder.canvas, i == 0, 0);
-        }
-        self.builder.canvas.present();
-
-        Toolkit {
+        let mut tk = Toolkit {
             run: true,
             canvas: self.builder.canvas,
             tabs: self.builder.tabs,
@@ -684,11 +2975,217 @@ impl TabBuilder {
             tab_pos: 0,
             max_tab_pos,
             redirect_input: false,
+            dirty: false,
             tk_event_queue: VecDeque::new(),
             old_xy: None,
             y_offset: 0,
-            y_velocity: 0,
-            line_height: attr.height as i32,
-        }
+            y_velocity: 0.0,
+            y_fraction: 0.0,
+            tab_scroll: 0,
+            animation: self.builder.animation,
+            last_tick: Instant::now(),
+            clock: None,
+            clock_ahead: Duration::ZERO,
+            shown_y: Tween::new(0.0),
+            shown_tab_scroll: Tween::new(0.0),
+            tab_slide: Tween::new(0.0),
+            animating: false,
+            focus_ring: Tween::new(1.0),
+            focus_from: Rect::new(0, 0, 1, 1),
+            last_focus: (0, 0, Rect::new(0, 0, 1, 1)),
+            line_height,
+            btn_repeat: Repeater::new(self.builder.repeat),
+            tab_repeat: Repeater::new(self.builder.repeat),
+            input_map: self.builder.input_map,
+            controller_subsystem: self.builder.controller_subsystem,
+            controllers: Vec::new(),
+            dialogs: VecDeque::new(),
+            dialog_callbacks: HashMap::new(),
+            width: width as i32,
+            height: height as i32,
+            theme: self.builder.theme,
+            text: self.builder.text,
+        };
+        tk.redraw();
+        Ok(tk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn theme() -> Theme {
+        Theme { margin: 10, row_spacing: 4, ..Theme::default() }
+    }
+
+    // focusable and doesn't need a renderer
+    fn picture(height: u32) -> Box<dyn Buttonish> {
+        Box::new(Picture {
+            name: Cow::Borrowed(""),
+            id: Id::unique(),
+            image: None,
+            size: (100, height),
+            rect: Some(Rect::new(0, 0, 100, height)),
+        })
+    }
+
+    fn tab(buttons: Page) -> Tab {
+        let mut tab = Tab {
+            name: Cow::Borrowed("tab"),
+            id: Id::unique(),
+            buttons,
+            btn_pos: 0,
+            max_btn_pos: 0,
+            text: None,
+            rect: Some(Rect::new(0, 0, 1, 1)),
+            callbacks: HashMap::new(),
+            parents: Vec::new(),
+            breadcrumb: None,
+            icon: None,
+            icon_width: 0,
+            content_height: 0,
+        };
+        tab.layout(&theme());
+        tab
+    }
+
+    #[test]
+    fn layout_stacks_widgets_inside_the_margins() {
+        let tab = tab(vec![picture(20), picture(20), picture(20)]);
+        let ys: Vec<i32> = tab.buttons.iter().map(|btn| btn.rect().y()).collect();
+        assert_eq!(ys, [10, 34, 58]);
+        assert_eq!(tab.content_height, 88);
+    }
+
+    #[test]
+    fn layout_moves_the_selection_off_separators() {
+        let tab = tab(vec![Box::new(Separator::new(10)), picture(20)]);
+        assert_eq!(tab.btn_pos, 1);
+    }
+
+    #[test]
+    fn hit_finds_the_widget_under_the_point() {
+        let tab = tab(vec![picture(20), picture(20)]);
+        assert_eq!(tab.hit(50, 10, 200, &theme()), Some(0));
+        assert_eq!(tab.hit(50, 29, 200, &theme()), Some(0));
+        assert_eq!(tab.hit(50, 34, 200, &theme()), Some(1));
+        assert_eq!(tab.hit(189, 40, 200, &theme()), Some(1));
+    }
+
+    #[test]
+    fn hit_misses_gaps_and_margins() {
+        let tab = tab(vec![picture(20), picture(20)]);
+        // above the first widget, between the two and below the last
+        assert_eq!(tab.hit(50, 5, 200, &theme()), None);
+        assert_eq!(tab.hit(50, 31, 200, &theme()), None);
+        assert_eq!(tab.hit(50, 60, 200, &theme()), None);
+        // left and right of the content
+        assert_eq!(tab.hit(9, 15, 200, &theme()), None);
+        assert_eq!(tab.hit(190, 15, 200, &theme()), None);
+    }
+
+    #[test]
+    fn step_goes_past_what_cant_be_selected() {
+        let tab = tab(vec![picture(20), Box::new(Separator::new(10)), picture(20), Box::new(Separator::new(10))]);
+        assert_eq!(tab.step(0, 1), 2);
+        assert_eq!(tab.step(2, -1), 0);
+        // nothing focusable further down, stays put
+        assert_eq!(tab.step(2, 1), 2);
+        // page sized steps stop at the last focusable one
+        assert_eq!(tab.step(0, 10), 2);
+    }
+
+    // a software renderer for the textures, it has to outlive the choice
+    // so it comes first and gets dropped last
+    fn choice(options: usize, selected: usize) -> (Canvas<sdl2::surface::Surface<'static>>, Choice) {
+        let canvas = sdl2::surface::Surface::new(1, 1, sdl2::pixels::PixelFormatEnum::RGBA8888).unwrap().into_canvas().unwrap();
+        let creator = canvas.texture_creator();
+        let texture = || creator.create_texture_target(sdl2::pixels::PixelFormatEnum::RGBA8888, 1, 1).unwrap();
+        let options = (0..options).map(|i| (Cow::Owned(i.to_string()), texture())).collect();
+        let choice = Choice::new(Cow::Borrowed("choice"), texture(), 0, 0, options, selected);
+        (canvas, choice)
+    }
+
+    #[test]
+    fn choice_wraps_around_the_options() {
+        let (_canvas, mut choice) = choice(3, 2);
+        let tab = Id::from("tab");
+        let id = choice.id().clone();
+        assert_eq!(choice.action(&tab, &InternalTkEvent::ChangeTabPos(1)), Some(TkEvent::ChoiceChange(tab.clone(), id.clone(), 0)));
+        assert_eq!(choice.action(&tab, &InternalTkEvent::ChangeTabPos(-1)), Some(TkEvent::ChoiceChange(tab.clone(), id.clone(), 2)));
+        assert_eq!(choice.action(&tab, &InternalTkEvent::ChangeTabPos(-1)), Some(TkEvent::ChoiceChange(tab.clone(), id.clone(), 1)));
+        // up and down stay in the widget
+        assert_eq!(choice.action(&tab, &InternalTkEvent::ChangeBtnPos(1)), Some(TkEvent::None));
+        assert_eq!(choice.action(&tab, &InternalTkEvent::Press), None);
+        assert!(!choice.grabbed);
+    }
+
+    #[test]
+    fn choice_without_options_does_nothing() {
+        let (_canvas, mut choice) = choice(0, 3);
+        let tab = Id::from("tab");
+        assert_eq!(choice.selected, 0);
+        assert_eq!(choice.action(&tab, &InternalTkEvent::ChangeTabPos(1)), Some(TkEvent::None));
+        assert_eq!(choice.action(&tab, &InternalTkEvent::ChangeTabPos(-1)), Some(TkEvent::None));
+        assert_eq!(choice.action(&tab, &InternalTkEvent::Back), None);
+    }
+
+    #[test]
+    fn widgets_inside_grids_are_counted_after_the_page() {
+        let mut buttons: Page = vec![
+            picture(20),
+            Box::new(Container::new(vec![picture(20), picture(20)], 2, 0)),
+            picture(20),
+        ];
+        let inner = buttons[1].page().unwrap()[1].id().clone();
+        let paths = widget_paths(&buttons);
+        let indices: Vec<Vec<usize>> = paths.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(indices, [vec![0], vec![1], vec![2], vec![1, 0], vec![1, 1]]);
+        assert_eq!(paths[4].1, inner);
+        assert_eq!(widget_at(&mut buttons, &[1, 1]).unwrap().id(), &inner);
+        assert!(widget_at(&mut buttons, &[0, 0]).is_none());
+        assert!(widget_at(&mut buttons, &[1, 2]).is_none());
+    }
+
+    #[test]
+    fn tab_bar_only_scrolls_when_it_overflows() {
+        assert_eq!(tab_bar_scroll(50, Rect::new(40, 0, 40, 20), 80, 100, 10), 0);
+        assert_eq!(tab_bar_arrows(0, 80, 100), (false, false));
+    }
+
+    #[test]
+    fn tab_bar_scrolls_the_tab_clear_of_the_arrows() {
+        // off the right end, then off the left end
+        assert_eq!(tab_bar_scroll(0, Rect::new(150, 0, 40, 20), 300, 100, 10), 100);
+        assert_eq!(tab_bar_scroll(100, Rect::new(20, 0, 40, 20), 300, 100, 10), 10);
+        // already on screen
+        assert_eq!(tab_bar_scroll(50, Rect::new(100, 0, 40, 20), 300, 100, 10), 50);
+    }
+
+    #[test]
+    fn tab_bar_scroll_stops_at_the_ends() {
+        assert_eq!(tab_bar_scroll(100, Rect::new(0, 0, 40, 20), 300, 100, 10), 0);
+        assert_eq!(tab_bar_scroll(0, Rect::new(260, 0, 40, 20), 300, 100, 10), 200);
+        assert_eq!(tab_bar_scroll(500, Rect::new(250, 0, 40, 20), 300, 100, 10), 200);
+    }
+
+    #[test]
+    fn tab_bar_arrows_show_what_is_hidden() {
+        assert_eq!(tab_bar_arrows(0, 300, 100), (false, true));
+        assert_eq!(tab_bar_arrows(100, 300, 100), (true, true));
+        assert_eq!(tab_bar_arrows(200, 300, 100), (true, false));
+    }
+
+    #[test]
+    fn remap_scales_between_ranges() {
+        assert_eq!(remap(5, 0, 10, 0, 100), 50);
+        assert_eq!(remap(-5, -10, 10, 0, 200), 50);
+        assert_eq!(remap(10, 0, 10, 20, 40), 40);
+    }
+
+    #[test]
+    fn remap_of_an_empty_range_is_the_start() {
+        assert_eq!(remap(3, 3, 3, 0, 100), 0);
     }
 }
@@ -0,0 +1,72 @@
+use sdl2::{
+    pixels::Color,
+    render::Texture,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub foreground: Color,
+    pub background: Color,
+    // selected items and grabbed sliders
+    pub accent: Color,
+    pub disabled: Color,
+    // between a label and whatever's next to it
+    pub padding: u32,
+    // between tab titles
+    pub tab_spacing: u32,
+    // around the widgets of a tab
+    pub margin: u32,
+    // between one widget and the next
+    pub row_spacing: u32,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Theme {
+        Theme {
+            foreground: Color::RGB(255, 255, 255),
+            background: Color::RGB(0, 0, 0),
+            accent: Color::RGB(255, 0, 0),
+            disabled: Color::RGB(128, 128, 128),
+            padding: 5,
+            tab_spacing: 10,
+            margin: 0,
+            row_spacing: 0,
+        }
+    }
+    pub fn light() -> Theme {
+        Theme {
+            foreground: Color::RGB(32, 32, 32),
+            background: Color::RGB(240, 240, 240),
+            accent: Color::RGB(0, 90, 200),
+            disabled: Color::RGB(160, 160, 160),
+            padding: 5,
+            tab_spacing: 10,
+            margin: 10,
+            row_spacing: 4,
+        }
+    }
+    pub fn high_contrast() -> Theme {
+        Theme {
+            foreground: Color::RGB(255, 255, 255),
+            background: Color::RGB(0, 0, 0),
+            accent: Color::RGB(255, 255, 0),
+            disabled: Color::RGB(170, 170, 170),
+            padding: 10,
+            tab_spacing: 20,
+            margin: 20,
+            row_spacing: 8,
+        }
+    }
+
+    // text is rendered white, so color mod gives it the theme's colors
+    pub fn tint(&self, text: &mut Texture, selected: bool) {
+        let color = if selected { self.accent } else { self.foreground };
+        text.set_color_mod(color.r, color.g, color.b);
+    }
+}
@@ -0,0 +1,160 @@
+use std::borrow::Cow;
+
+use sdl2::{
+    rect::Rect,
+    render::{Canvas, Texture},
+    video::Window,
+};
+
+use crate::{Buttonish, Id, InternalTkEvent, TkEvent, Theme, ui_scale};
+use crate::font::TextRenderer;
+
+// What a widget gets while it's selected, or everything while it has grabbed input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WidgetInput {
+    // left/right, one step or more
+    Horizontal(i32),
+    // up/down, a whole page at once for page up/down
+    Vertical(i32),
+    Press,
+    Back,
+    // relative to the widget's top left corner
+    Touch(i32, i32),
+}
+
+// What a widget does with an input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Response {
+    None,
+    // shows up as TkEvent::Custom(tab, widget, value)
+    Emit(i32),
+    // lets go of grabbed input
+    Release,
+    EmitAndRelease(i32),
+}
+
+// Everything a widget needs for drawing and measuring itself
+pub struct DrawContext<'a> {
+    canvas: &'a mut Canvas<Window>,
+    theme: &'a Theme,
+    text: &'a mut TextRenderer,
+}
+
+impl<'a> DrawContext<'a> {
+    pub(crate) fn new(canvas: &'a mut Canvas<Window>, theme: &'a Theme, text: &'a mut TextRenderer) -> DrawContext<'a> {
+        DrawContext {
+            canvas,
+            theme,
+            text,
+        }
+    }
+    pub fn canvas(&mut self) -> &mut Canvas<Window> {
+        self.canvas
+    }
+    pub fn theme(&self) -> &Theme {
+        self.theme
+    }
+    // 1.0 at 480 pixels, multiply sizes by this to match the text
+    pub fn scale(&self) -> f32 {
+        ui_scale(self.canvas)
+    }
+    // in the toolkit's font, white so that it can be tinted with Theme::tint
+    pub fn render_text(&mut self, text: &str) -> Texture {
+        let scale = self.scale();
+        self.text.render(text, scale)
+    }
+    pub(crate) fn render_wrapped(&mut self, text: &str) -> Texture {
+        let scale = self.scale();
+        let (width, _) = self.canvas.output_size().unwrap();
+        self.text.render_wrapped(text, scale, width.saturating_sub(self.theme.margin * 2))
+    }
+}
+
+// Custom widgets, added with TabBuilder::widget.
+//
+// Widgets that capture input get everything after being pressed, including
+// that first Press, until they answer with Release or EmitAndRelease.
+pub trait Widget {
+    // width and height, asked when the widget is added and on Toolkit::update_widget
+    fn measure(&mut self, ctx: &mut DrawContext) -> (u32, u32);
+    // `rect` is where the widget goes this frame, with scrolling applied
+    fn draw(&mut self, ctx: &mut DrawContext, rect: Rect, selected: bool);
+    fn input(&mut self, input: WidgetInput) -> Response;
+    fn focusable(&self) -> bool { true }
+    fn captures_input(&self) -> bool { false }
+    fn name(&self) -> &str { "" }
+}
+
+// makes a Widget look like one of ours
+pub(crate) struct Custom {
+    widget: Box<dyn Widget>,
+    id: Id,
+    rect: Option<Rect>,
+    // where it was last drawn in window coordinates, for touch input
+    screen_rect: Rect,
+    released: bool,
+}
+
+impl Custom {
+    pub fn new(mut widget: Box<dyn Widget>, ctx: &mut DrawContext) -> Custom {
+        let (width, height) = widget.measure(ctx);
+        Custom {
+            widget,
+            id: Id::unique(),
+            rect: Some(Rect::new(0, 0, width, height)),
+            screen_rect: Rect::new(0, 0, width, height),
+            released: false,
+        }
+    }
+}
+
+impl Buttonish for Custom {
+    fn draw(&mut self, canvas: &mut Canvas<Window>, theme: &Theme, text: &mut TextRenderer, selected: bool, offset_y: i32) {
+        let mut rect = self.rect.unwrap();
+        rect.set_y(rect.y() + offset_y);
+        let viewport = canvas.viewport();
+        self.screen_rect = Rect::new(rect.x() + viewport.x(), rect.y() + viewport.y(), rect.width(), rect.height());
+        self.widget.draw(&mut DrawContext::new(canvas, theme, text), rect, selected);
+    }
+    fn captures_input(&self) -> bool { self.widget.captures_input() }
+    fn holds_input(&self) -> bool { !self.released }
+    fn focusable(&self) -> bool { self.widget.focusable() }
+    fn action(&mut self, tab: &Id, ev: &InternalTkEvent) -> Option<TkEvent> {
+        let input = match ev {
+            // the press that grabbed input
+            InternalTkEvent::Dummy | InternalTkEvent::Press => WidgetInput::Press,
+            InternalTkEvent::Back => WidgetInput::Back,
+            InternalTkEvent::ChangeTabPos(d) => WidgetInput::Horizontal(*d),
+            InternalTkEvent::ChangeBtnPos(d) => WidgetInput::Vertical(*d),
+            InternalTkEvent::TouchPress(x, y) => WidgetInput::Touch(x - self.screen_rect.x(), y - self.screen_rect.y()),
+            _ => return Some(TkEvent::None),
+        };
+        let grabbed = self.widget.captures_input();
+        self.released = false;
+        match self.widget.input(input) {
+            Response::None if grabbed => Some(TkEvent::None),
+            Response::None => None,
+            Response::Emit(value) => Some(TkEvent::Custom(tab.clone(), self.id.clone(), value)),
+            Response::Release => None,
+            Response::EmitAndRelease(value) => {
+                self.released = true;
+                Some(TkEvent::Custom(tab.clone(), self.id.clone(), value))
+            },
+        }
+    }
+    fn name(&self) -> &str { self.widget.name() }
+    fn id(&self) -> &Id { &self.id }
+    fn set_id(&mut self, id: Id) { self.id = id }
+    fn rect(&self) -> Rect { self.rect.unwrap() }
+    // custom widgets draw their own labels
+    fn has_label(&self) -> bool { false }
+    fn set_label(&mut self, _: Cow<'static, str>, _: Texture, _: u32) {}
+    fn set_y(&mut self, y: i32) { self.rect.as_mut().unwrap().set_y(y) }
+    fn rescale(&mut self, ctx: &mut DrawContext) { self.measure(ctx); }
+    fn measure(&mut self, ctx: &mut DrawContext) -> bool {
+        let (width, height) = self.widget.measure(ctx);
+        let rect = self.rect.unwrap();
+        self.rect = Some(Rect::new(rect.x(), rect.y(), width, height));
+        true
+    }
+}
@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use gamepad_gui::{ToolkitBuilder, Toolkit, InputAction};
+
+// sdl can only be initialized once at a time per process
+static SDL: Mutex<()> = Mutex::new(());
+
+fn settings() -> Toolkit {
+    ToolkitBuilder::headless("golden")
+        .tab("settings")
+        .toggle("toggle")
+        .slider("slider", 3, 0, 5)
+        .button("button")
+        .tab("other")
+        .button("other button")
+        .build()
+}
+
+fn temp_png(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("gamepad_gui_{}_{}.png", std::process::id(), name))
+}
+
+#[test]
+fn missing_golden_is_an_error() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    if std::env::var_os("GAMEPAD_GUI_UPDATE_GOLDEN").is_some() {
+        return;
+    }
+    let mut tk = settings();
+    let path = temp_png("missing");
+    assert!(tk.compare_frame(&path, 0).is_err());
+    assert!(!path.exists());
+}
+
+#[test]
+fn frame_matches_itself_until_something_changes() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tk = settings();
+    let path = temp_png("self");
+    tk.save_frame(&path).unwrap();
+    assert_eq!(tk.compare_frame(&path, 0), Ok(true));
+
+    // the slider is selected instead of the toggle
+    tk.inject(InputAction::Down);
+    tk.set_animations(false);
+    tk.tick();
+    let result = tk.compare_frame(&path, 0);
+    std::fs::remove_file(&path).unwrap();
+    if std::env::var_os("GAMEPAD_GUI_UPDATE_GOLDEN").is_none() {
+        assert_eq!(result, Ok(false));
+    }
+}
+
+// rendered with the embedded font so it doesn't depend on what's installed, regenerate with
+// GAMEPAD_GUI_UPDATE_GOLDEN=1 cargo test -p gamepad_gui --features embedded-font --test golden
+#[cfg(feature = "embedded-font")]
+#[test]
+fn tab_and_slider_match_golden() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tk = ToolkitBuilder::headless("golden")
+        .font(gamepad_gui::FontConfig::new())
+        .tab("settings")
+        .toggle("toggle")
+        .slider("slider", 3, 0, 5)
+        .button("button")
+        .tab("other")
+        .button("other button")
+        .build();
+    let golden = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden/settings.png");
+    assert_eq!(tk.compare_frame(&golden, 2), Ok(true));
+}
+
+#[test]
+fn window_options_apply_when_built() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tk = ToolkitBuilder::headless("golden")
+        .size(320, 240)
+        .tab("settings")
+        .button("button")
+        .build();
+    let (width, height, _) = tk.frame();
+    assert_eq!((width, height), (320, 240));
+}
+
+#[test]
+fn missing_display_is_an_error() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let tk = ToolkitBuilder::headless("golden")
+        .display(99)
+        .tab("settings")
+        .button("button")
+        .try_build();
+    assert!(tk.is_err());
+}
+
+#[test]
+#[cfg(not(feature = "embedded-font"))]
+fn missing_font_is_an_error() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let tk = ToolkitBuilder::headless("golden")
+        .font(gamepad_gui::FontConfig::new().path("/nonexistent.ttf"))
+        .tab("settings")
+        .button("button")
+        .try_build();
+    assert!(tk.is_err());
+}
+
+#[test]
+fn labels_with_nul_bytes_still_render() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tk = ToolkitBuilder::headless("golden")
+        .tab("set\0tings")
+        .button("\0")
+        .build();
+    assert!(tk.set_button_label(0, 0, "but\0ton"));
+    tk.frame();
+}
@@ -0,0 +1,219 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use gamepad_gui::{ToolkitBuilder, Toolkit, TkEvent, InputAction, Id, Step};
+use gamepad_gui::{Widget, WidgetInput, Response, DrawContext};
+use gamepad_gui::sdl2::rect::Rect;
+
+// sdl can only be initialized once at a time per process
+static SDL: Mutex<()> = Mutex::new(());
+
+fn toolkit() -> Toolkit {
+    ToolkitBuilder::headless("script")
+        .tab("first").tab_id("first")
+        .toggle("toggle").with_id("toggle")
+        .button("button").with_id("button")
+        .tab("second").tab_id("second")
+        .button("other").with_id("other")
+        .build()
+}
+
+#[test]
+fn injected_actions_come_out_as_events() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tk = toolkit();
+    let events = tk.play(&[
+        (Duration::ZERO, InputAction::Press),
+        (Duration::ZERO, InputAction::Down),
+        (Duration::ZERO, InputAction::Press),
+        (Duration::from_millis(100), InputAction::Right),
+        (Duration::ZERO, InputAction::Back),
+    ]);
+    assert_eq!(events, vec![
+        TkEvent::ToggleChange(Id::from("first"), Id::from("toggle"), true),
+        TkEvent::ButtonSelect(Id::from("first"), Id::from("button")),
+        TkEvent::ButtonPress(Id::from("first"), Id::from("button")),
+        TkEvent::TabChange(Id::from("second")),
+        TkEvent::Back,
+    ]);
+}
+
+#[test]
+fn delays_do_not_take_real_time() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tk = toolkit();
+    let start = Instant::now();
+    let events = tk.play(&[
+        (Duration::from_secs(10), InputAction::Down),
+        (Duration::from_secs(10), InputAction::Up),
+    ]);
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert_eq!(events, vec![
+        TkEvent::ButtonSelect(Id::from("first"), Id::from("button")),
+        TkEvent::ButtonSelect(Id::from("first"), Id::from("toggle")),
+    ]);
+}
+
+#[test]
+fn held_directions_repeat_until_released() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tk = ToolkitBuilder::headless("script")
+        .tab("list").tab_id("list")
+        .button("one").with_id("one")
+        .button("two").with_id("two")
+        .button("three").with_id("three")
+        .button("four").with_id("four")
+        .button("five").with_id("five")
+        .build();
+    // past the repeat delay, but not long enough to reach the end of the list
+    let events = tk.play(&[
+        (Duration::ZERO, Step::Hold(InputAction::Down)),
+        (Duration::from_millis(450), Step::Release(InputAction::Down)),
+        (Duration::from_secs(1), Step::Tap(InputAction::Press)),
+    ]);
+    assert_eq!(events, vec![
+        TkEvent::ButtonSelect(Id::from("list"), Id::from("two")),
+        TkEvent::ButtonSelect(Id::from("list"), Id::from("three")),
+        TkEvent::ButtonPress(Id::from("list"), Id::from("three")),
+    ]);
+}
+
+fn with_submenu() -> Toolkit {
+    ToolkitBuilder::headless("script")
+        .tab("first").tab_id("first")
+        .button("before").with_id("before")
+        .submenu("more")
+        .toggle("inner").with_id("inner")
+        .end_submenu().with_id("more")
+        .toggle("after").with_id("after")
+        .build()
+}
+
+fn open_submenu(tk: &mut Toolkit) {
+    let events = tk.play(&[
+        (Duration::ZERO, InputAction::Down),
+        (Duration::ZERO, InputAction::Press),
+    ]);
+    assert_eq!(events.last(), Some(&TkEvent::SubmenuOpen(Id::from("first"), Id::from("more"))));
+}
+
+#[test]
+fn widget_indices_count_from_the_first_page_with_a_submenu_open() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tk = with_submenu();
+    let first = Id::from("first");
+    let indices = |tk: &Toolkit| ["before", "more", "after", "inner"].map(|id| tk.widget_index(&first, &Id::from(id)));
+    let closed = indices(&tk);
+    assert_eq!(closed, [Some((0, 0)), Some((0, 1)), Some((0, 2)), Some((0, 3))]);
+    open_submenu(&mut tk);
+    assert_eq!(indices(&tk), closed);
+
+    // the toggle on the first page and the one in the submenu
+    assert!(tk.set_toggle(0, 2, true));
+    assert!(tk.set_toggle(0, 3, true));
+    let events = tk.play(&[
+        (Duration::ZERO, InputAction::Press),
+        (Duration::ZERO, InputAction::Back),
+        (Duration::ZERO, InputAction::Down),
+        (Duration::ZERO, InputAction::Press),
+    ]);
+    assert_eq!(events, vec![
+        TkEvent::ToggleChange(first.clone(), Id::from("inner"), false),
+        TkEvent::SubmenuClose(first.clone(), Id::from("more")),
+        TkEvent::ButtonSelect(first.clone(), Id::from("after")),
+        TkEvent::ToggleChange(first.clone(), Id::from("after"), false),
+    ]);
+}
+
+#[test]
+fn inserting_and_removing_with_a_submenu_open_changes_the_first_page() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tk = with_submenu();
+    let first = Id::from("first");
+    open_submenu(&mut tk);
+
+    assert!(tk.remove_button(0, 0));
+    assert!(tk.insert_button(0, 1, "new", "new"));
+    assert_eq!(tk.widget_index(&first, &Id::from("more")), Some((0, 0)));
+    assert_eq!(tk.widget_index(&first, &Id::from("new")), Some((0, 1)));
+    assert_eq!(tk.widget_index(&first, &Id::from("inner")), Some((0, 3)));
+
+    // back on the submenu it was opened from
+    let events = tk.play(&[
+        (Duration::ZERO, InputAction::Back),
+        (Duration::ZERO, InputAction::Down),
+    ]);
+    assert_eq!(events, vec![
+        TkEvent::SubmenuClose(first.clone(), Id::from("more")),
+        TkEvent::ButtonSelect(first.clone(), Id::from("new")),
+    ]);
+}
+
+#[test]
+fn removing_an_open_submenu_closes_it() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tk = with_submenu();
+    let first = Id::from("first");
+    open_submenu(&mut tk);
+
+    assert!(tk.remove_button(0, 1));
+    assert_eq!(tk.widget_index(&first, &Id::from("inner")), None);
+    assert!(!tk.back());
+    let events = tk.play(&[(Duration::ZERO, InputAction::Press)]);
+    assert_eq!(events, vec![TkEvent::ToggleChange(first.clone(), Id::from("after"), true)]);
+}
+
+// grabs input but lets go on the same press it was grabbed with
+struct OneShot;
+
+impl Widget for OneShot {
+    fn measure(&mut self, _: &mut DrawContext) -> (u32, u32) { (100, 20) }
+    fn draw(&mut self, _: &mut DrawContext, _: Rect, _: bool) {}
+    fn input(&mut self, input: WidgetInput) -> Response {
+        match input {
+            WidgetInput::Press => Response::EmitAndRelease(1),
+            _ => Response::None,
+        }
+    }
+    fn captures_input(&self) -> bool { true }
+}
+
+#[test]
+fn custom_widgets_can_release_input_on_the_grabbing_press() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tk = ToolkitBuilder::headless("script")
+        .tab("first").tab_id("first")
+        .widget(Box::new(OneShot)).with_id("custom")
+        .button("button").with_id("button")
+        .build();
+    let first = Id::from("first");
+    let events = tk.play(&[
+        (Duration::ZERO, InputAction::Press),
+        (Duration::ZERO, InputAction::Down),
+    ]);
+    assert_eq!(events, vec![
+        TkEvent::Custom(first.clone(), Id::from("custom"), 1),
+        TkEvent::ButtonSelect(first.clone(), Id::from("button")),
+    ]);
+    // there's no label to change
+    assert!(!tk.set_button_label(0, 0, "label"));
+    assert!(tk.set_button_label(0, 1, "label"));
+}
+
+#[test]
+fn left_and_right_stay_inside_a_row() {
+    let _sdl = SDL.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tk = ToolkitBuilder::headless("script")
+        .tab("first").tab_id("first")
+        .row()
+        .button("only").with_id("only")
+        .end_row()
+        .tab("second").tab_id("second")
+        .build();
+    let events = tk.play(&[
+        (Duration::ZERO, InputAction::Right),
+        (Duration::ZERO, InputAction::Left),
+        (Duration::ZERO, InputAction::Press),
+    ]);
+    assert_eq!(events, vec![TkEvent::ButtonPress(Id::from("first"), Id::from("only"))]);
+}